@@ -0,0 +1,273 @@
+//! `#[derive(AocParse)]`, which implements `FromStr` for a puzzle input
+//! record from a pattern such as `"move {quantity} from {from} to {to}"`.
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::spanned::Spanned as _;
+use syn::{Attribute, Data, DeriveInput, Fields, LitStr, Type};
+
+/// Implements `FromStr` with `aoc2022::ParseError` errors by matching each
+/// line against a pattern given with `#[aoc(pattern = "...")]`.
+///
+/// Text in the pattern must match exactly, and `{field}` (or `{0}` for tuple
+/// fields) captures everything up to the next piece of text, or the end of
+/// the line, and parses it with the field's own `FromStr`. Structs take the
+/// attribute on the type, and enums on each variant; the first variant whose
+/// text matches is used.
+///
+/// ```ignore
+/// #[derive(AocParse)]
+/// enum Instruction {
+///     #[aoc(pattern = "addx {0}")]
+///     Add(isize),
+///     #[aoc(pattern = "noop")]
+///     Noop,
+/// }
+/// ```
+#[proc_macro_derive(AocParse, attributes(aoc))]
+pub fn derive_aoc_parse(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = syn::parse_macro_input!(input as DeriveInput);
+    expand(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let (matchers, descriptions) = match &input.data {
+        Data::Struct(data) => {
+            let pattern = pattern(&input.attrs, input.span())?;
+            let matcher = matcher(&pattern, quote!(Self), &data.fields)?;
+            (vec![matcher], vec![pattern.description(&data.fields)])
+        }
+        Data::Enum(data) => {
+            let mut matchers = vec![];
+            let mut descriptions = vec![];
+            for variant in data.variants.iter() {
+                let pattern = pattern(&variant.attrs, variant.span())?;
+                let ident = &variant.ident;
+                matchers.push(matcher(&pattern, quote!(Self::#ident), &variant.fields)?);
+                descriptions.push(pattern.description(&variant.fields));
+            }
+            (matchers, descriptions)
+        }
+        Data::Union(_) => {
+            return Err(syn::Error::new(
+                input.span(),
+                "AocParse cannot be derived for unions",
+            ))
+        }
+    };
+    Ok(quote! {
+        impl #impl_generics ::std::str::FromStr for #name #ty_generics #where_clause {
+            type Err = ::aoc2022::parse::ParseError;
+
+            fn from_str(s: &str) -> ::std::result::Result<Self, Self::Err> {
+                #(
+                    if let ::std::option::Option::Some(result) = (#matchers)(s) {
+                        return result;
+                    }
+                )*
+                ::std::result::Result::Err(::aoc2022::parse::ParseError::at(
+                    s,
+                    s,
+                    ::aoc2022::parse::one_of::<&str>(&[#(#descriptions),*]),
+                ))
+            }
+        }
+    })
+}
+
+/// A piece of a pattern.
+enum Piece {
+    Text(String),
+    /// A named field, or the index of a tuple field.
+    Field(String),
+}
+
+struct Pattern {
+    pieces: Vec<Piece>,
+    span: proc_macro2::Span,
+}
+
+impl Pattern {
+    fn parse(lit: &LitStr) -> syn::Result<Self> {
+        let error = |message: &str| syn::Error::new(lit.span(), message);
+        let value = lit.value();
+        let mut pieces = vec![];
+        let mut rest = value.as_str();
+        while !rest.is_empty() {
+            if let Some(after) = rest.strip_prefix('{') {
+                let end = after
+                    .find('}')
+                    .ok_or_else(|| error("unclosed `{` in pattern"))?;
+                let field = after[..end].trim();
+                if field.is_empty() {
+                    return Err(error("empty `{}` in pattern"));
+                }
+                if matches!(pieces.last(), Some(Piece::Field(_))) {
+                    return Err(error("fields in a pattern must be separated by some text"));
+                }
+                pieces.push(Piece::Field(field.to_string()));
+                rest = &after[end + 1..];
+            } else {
+                let end = rest.find('{').unwrap_or(rest.len());
+                if rest[..end].contains('}') {
+                    return Err(error("unopened `}` in pattern"));
+                }
+                pieces.push(Piece::Text(rest[..end].to_string()));
+                rest = &rest[end..];
+            }
+        }
+        Ok(Self {
+            pieces,
+            span: lit.span(),
+        })
+    }
+
+    /// How the pattern reads in error messages, e.g. "`addx <isize>`".
+    fn description(&self, fields: &Fields) -> String {
+        let mut description = String::from("`");
+        for piece in self.pieces.iter() {
+            match piece {
+                Piece::Text(text) => description.push_str(text),
+                Piece::Field(name) => {
+                    let label = match fields {
+                        Fields::Named(_) => name.clone(),
+                        _ => field_type(fields, name)
+                            .map(|ty| quote!(#ty).to_string())
+                            .unwrap_or_else(|| name.clone()),
+                    };
+                    description.push_str(&format!("<{}>", label));
+                }
+            }
+        }
+        description.push('`');
+        description
+    }
+}
+
+/// Reads the `#[aoc(pattern = "...")]` attribute from `attrs`.
+fn pattern(attrs: &[Attribute], span: proc_macro2::Span) -> syn::Result<Pattern> {
+    let mut pattern = None;
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("aoc")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("pattern") {
+                pattern = Some(Pattern::parse(&meta.value()?.parse()?)?);
+                Ok(())
+            } else {
+                Err(meta.error("expected `pattern = \"...\"`"))
+            }
+        })?;
+    }
+    pattern.ok_or_else(|| syn::Error::new(span, "expected #[aoc(pattern = \"...\")]"))
+}
+
+fn field_type<'a>(fields: &'a Fields, name: &str) -> Option<&'a Type> {
+    match fields {
+        Fields::Named(named) => named
+            .named
+            .iter()
+            .find(|field| field.ident.as_ref().is_some_and(|ident| ident == name))
+            .map(|field| &field.ty),
+        Fields::Unnamed(unnamed) => name
+            .parse::<usize>()
+            .ok()
+            .and_then(|i| unnamed.unnamed.iter().nth(i))
+            .map(|field| &field.ty),
+        Fields::Unit => None,
+    }
+}
+
+/// A closure from the line to `None` if it does not fit the pattern, or else
+/// to the result of parsing each field and building `constructor`.
+fn matcher(
+    pattern: &Pattern,
+    constructor: TokenStream,
+    fields: &Fields,
+) -> syn::Result<TokenStream> {
+    let mut steps = vec![];
+    let mut values = vec![];
+    let mut seen = vec![];
+    for (i, piece) in pattern.pieces.iter().enumerate() {
+        match piece {
+            Piece::Text(text) => steps.push(quote! {
+                let rest = ::aoc2022::parse::literal(rest, #text)?;
+            }),
+            Piece::Field(name) => {
+                let ty = field_type(fields, name).ok_or_else(|| {
+                    syn::Error::new(pattern.span, format!("no field `{}` to parse into", name))
+                })?;
+                if seen.contains(name) {
+                    return Err(syn::Error::new(
+                        pattern.span,
+                        format!("field `{}` appears more than once", name),
+                    ));
+                }
+                seen.push(name.clone());
+
+                let until = match pattern.pieces.get(i + 1) {
+                    Some(Piece::Text(text)) => quote!(::std::option::Option::Some(#text)),
+                    _ => quote!(::std::option::Option::None),
+                };
+                let var = format_ident!("field_{}", name);
+                let type_name = quote!(#ty).to_string();
+                steps.push(quote! {
+                    let (#var, rest) = ::aoc2022::parse::field(rest, #until)?;
+                });
+                values.push(quote! {
+                    let #var = ::aoc2022::parse::value::<#ty>(s, #var, #type_name)?;
+                });
+            }
+        }
+    }
+
+    let all_fields = match fields {
+        Fields::Named(named) => named
+            .named
+            .iter()
+            .map(|field| field.ident.as_ref().unwrap().to_string())
+            .collect(),
+        Fields::Unnamed(unnamed) => (0..unnamed.unnamed.len()).map(|i| i.to_string()).collect(),
+        Fields::Unit => vec![],
+    };
+    if let Some(missing) = all_fields.iter().find(|field| !seen.contains(field)) {
+        return Err(syn::Error::new(
+            pattern.span,
+            format!("field `{}` is missing from the pattern", missing),
+        ));
+    }
+
+    let construct = match fields {
+        Fields::Named(_) => {
+            let names = all_fields.iter().map(|name| format_ident!("{}", name));
+            let vars = all_fields
+                .iter()
+                .map(|name| format_ident!("field_{}", name));
+            quote!(#constructor { #(#names: #vars),* })
+        }
+        Fields::Unnamed(_) => {
+            let vars = all_fields
+                .iter()
+                .map(|name| format_ident!("field_{}", name));
+            quote!(#constructor(#(#vars),*))
+        }
+        Fields::Unit => constructor,
+    };
+
+    Ok(quote! {
+        |s: &str| -> ::std::option::Option<::std::result::Result<Self, ::aoc2022::parse::ParseError>> {
+            let rest = s;
+            #(#steps)*
+            if !rest.is_empty() {
+                return ::std::option::Option::None;
+            }
+            ::std::option::Option::Some((|| -> ::std::result::Result<Self, ::aoc2022::parse::ParseError> {
+                #(#values)*
+                ::std::result::Result::Ok(#construct)
+            })())
+        }
+    })
+}
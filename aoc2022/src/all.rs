@@ -0,0 +1,128 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use futures_concurrency::prelude::*;
+
+use crate::answer::{Answer, ExpectedAnswers, ANSWERS_FILE_NAME};
+use crate::input;
+use crate::solution::SOLUTIONS;
+
+/// The outcome of solving one part of one day against one input.
+#[derive(Debug)]
+pub struct Run {
+    pub day: usize,
+    pub part: usize,
+    /// The input name, such as `main` or `sample`.
+    pub input: String,
+    /// Wall-clock time, which includes contention with every other run.
+    pub elapsed: Duration,
+    pub result: anyhow::Result<Answer>,
+    /// The recorded answer for this input and part, if there is one.
+    pub expected: Option<Answer>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    /// The answer matches the recorded one.
+    Ok,
+    /// There is no recorded answer to check against.
+    Unchecked,
+    /// The answer differs from the recorded one.
+    Wrong,
+    /// The part returned an error or panicked.
+    Failed,
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Self::Ok => "ok",
+            Self::Unchecked => "unchecked",
+            Self::Wrong => "WRONG",
+            Self::Failed => "FAILED",
+        };
+        f.pad(s)
+    }
+}
+
+impl Run {
+    pub fn status(&self) -> Status {
+        match (&self.result, &self.expected) {
+            (Err(_), _) => Status::Failed,
+            (Ok(_), None) => Status::Unchecked,
+            (Ok(answer), Some(expected)) if answer == expected => Status::Ok,
+            (Ok(_), Some(_)) => Status::Wrong,
+        }
+    }
+}
+
+/// Solves every part of every registered day against every input under
+/// `input_root/dayN/`, all at once. Each part runs on its own task, so one
+/// failing or panicking part does not stop the others. A day whose inputs or
+/// recorded answers cannot be read gets a failed run for each of its parts,
+/// named `-`, in place of its inputs.
+pub async fn run_all(input_root: &Path) -> Vec<Run> {
+    let mut runs = vec![];
+    let mut unreadable = vec![];
+    for &solution in SOLUTIONS {
+        let dir = input::day_dir(input_root, solution.day());
+        let (expected, inputs) = match load_day(&dir) {
+            Ok(day) => day,
+            Err(err) => {
+                for part in 1..=solution.parts().len() {
+                    unreadable.push(Run {
+                        day: solution.day(),
+                        part,
+                        input: String::from("-"),
+                        elapsed: Duration::ZERO,
+                        result: Err(anyhow::anyhow!("{:#}", err)),
+                        expected: None,
+                    });
+                }
+                continue;
+            }
+        };
+
+        for (input, path) in inputs {
+            for part in 1..=solution.parts().len() {
+                let expected = expected.get(&input, part).cloned();
+                let (input, path) = (input.clone(), path.clone());
+                runs.push(async move {
+                    let task = tokio::spawn(async move {
+                        let start = Instant::now();
+                        let result = solution.run(part, &path).await;
+                        (result, start.elapsed())
+                    });
+                    let (result, elapsed) = task.await.unwrap_or_else(|err| {
+                        (Err(anyhow::anyhow!("Panicked: {}", err)), Duration::ZERO)
+                    });
+                    Run {
+                        day: solution.day(),
+                        part,
+                        input,
+                        elapsed,
+                        result,
+                        expected,
+                    }
+                });
+            }
+        }
+    }
+
+    let mut runs = runs.join().await;
+    runs.extend(unreadable);
+    // Stable, so each day keeps its own order.
+    runs.sort_by_key(|run| run.day);
+    runs
+}
+
+/// The recorded answers in `dir`, if there are any, and its inputs.
+fn load_day(dir: &Path) -> anyhow::Result<(ExpectedAnswers, Vec<(String, PathBuf)>)> {
+    let expected = if dir.join(ANSWERS_FILE_NAME).exists() {
+        ExpectedAnswers::load(dir)?
+    } else {
+        ExpectedAnswers::default()
+    };
+    Ok((expected, input::list(dir)?))
+}
@@ -0,0 +1,151 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+
+use anyhow::Context as _;
+use serde::de::{self, Deserialize, Deserializer, SeqAccess, Visitor};
+use serde::ser::{Serialize, SerializeSeq as _, Serializer};
+
+/// The result of running any part of any day.
+///
+/// Serializes as a plain number, string or array of image rows so answers
+/// files stay readable.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Answer {
+    Integer(i128),
+    Text(String),
+    /// A rendered picture, such as the CRT screen of day 10, one row per line.
+    Image(Vec<String>),
+}
+
+impl Answer {
+    /// Builds an `Image` from a multi-line rendering.
+    pub fn image(rendered: impl AsRef<str>) -> Self {
+        Self::Image(rendered.as_ref().lines().map(str::to_string).collect())
+    }
+
+    /// Whether the answer needs more than one line to display.
+    pub fn is_multiline(&self) -> bool {
+        matches!(self, Self::Image(rows) if rows.len() > 1)
+    }
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Integer(i) => write!(f, "{}", i),
+            Self::Text(s) => write!(f, "{}", s),
+            Self::Image(rows) => write!(f, "{}", rows.join("\n")),
+        }
+    }
+}
+
+macro from_integer($($ty:ty),*) {
+    $(
+        impl From<$ty> for Answer {
+            fn from(value: $ty) -> Self {
+                Self::Integer(value as i128)
+            }
+        }
+    )*
+}
+
+from_integer!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize, i128);
+
+impl From<String> for Answer {
+    fn from(value: String) -> Self {
+        Self::Text(value)
+    }
+}
+
+impl From<&str> for Answer {
+    fn from(value: &str) -> Self {
+        Self::Text(value.to_string())
+    }
+}
+
+impl Serialize for Answer {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::Integer(i) => serializer.serialize_i128(*i),
+            Self::Text(s) => serializer.serialize_str(s),
+            Self::Image(rows) => {
+                let mut seq = serializer.serialize_seq(Some(rows.len()))?;
+                for row in rows {
+                    seq.serialize_element(row)?;
+                }
+                seq.end()
+            }
+        }
+    }
+}
+
+struct AnswerVisitor;
+
+impl<'de> Visitor<'de> for AnswerVisitor {
+    type Value = Answer;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "an integer, a string or an array of image rows")
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<Answer, E> {
+        Ok(Answer::Integer(v as i128))
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Answer, E> {
+        Ok(Answer::Integer(v as i128))
+    }
+
+    fn visit_i128<E: de::Error>(self, v: i128) -> Result<Answer, E> {
+        Ok(Answer::Integer(v))
+    }
+
+    fn visit_u128<E: de::Error>(self, v: u128) -> Result<Answer, E> {
+        i128::try_from(v)
+            .map(Answer::Integer)
+            .map_err(|_| E::custom(format!("{} does not fit in an i128", v)))
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Answer, E> {
+        Ok(Answer::Text(v.to_string()))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Answer, A::Error> {
+        let mut rows = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(row) = seq.next_element::<String>()? {
+            rows.push(row);
+        }
+        Ok(Answer::Image(rows))
+    }
+}
+
+impl<'de> Deserialize<'de> for Answer {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(AnswerVisitor)
+    }
+}
+
+/// The name of the file next to a day's inputs that records their answers.
+pub const ANSWERS_FILE_NAME: &str = "answers.json";
+
+/// The known answers for a day's inputs, keyed by input name (the file stem,
+/// such as `sample1`) and then by part.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(transparent)]
+pub struct ExpectedAnswers(pub BTreeMap<String, BTreeMap<usize, Answer>>);
+
+impl ExpectedAnswers {
+    /// Reads the answers file in the day's input directory `dir`.
+    pub fn load(dir: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = dir.as_ref().join(ANSWERS_FILE_NAME);
+        let contents = std::fs::read_to_string(&path)
+            .with_context(|| format!("Could not read {}.", path.display()))?;
+        serde_json::from_str(&contents)
+            .with_context(|| format!("Could not parse {}.", path.display()))
+    }
+
+    pub fn get(&self, input: &str, part: usize) -> Option<&Answer> {
+        self.0.get(input).and_then(|parts| parts.get(&part))
+    }
+}
@@ -0,0 +1,159 @@
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use anyhow::Context as _;
+use serde::{Deserialize, Serialize};
+
+use crate::solution::Solution;
+
+/// Summary of a set of timings, in nanoseconds so baselines stay portable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Stats {
+    pub min_ns: u64,
+    pub median_ns: u64,
+    pub max_ns: u64,
+}
+
+impl Stats {
+    /// Summarizes `timings`, which must not be empty. With an even number of
+    /// timings, the median is the higher of the middle two.
+    pub fn new(mut timings: Vec<Duration>) -> Self {
+        timings.sort_unstable();
+        let nanos = |d: Duration| u64::try_from(d.as_nanos()).unwrap_or(u64::MAX);
+        Self {
+            min_ns: nanos(timings[0]),
+            median_ns: nanos(timings[timings.len() / 2]),
+            max_ns: nanos(timings[timings.len() - 1]),
+        }
+    }
+
+    pub fn min(&self) -> Duration {
+        Duration::from_nanos(self.min_ns)
+    }
+
+    pub fn median(&self) -> Duration {
+        Duration::from_nanos(self.median_ns)
+    }
+
+    pub fn max(&self) -> Duration {
+        Duration::from_nanos(self.max_ns)
+    }
+}
+
+/// The parse and solve timings of one part of one day.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Measurement {
+    pub day: usize,
+    pub part: usize,
+    pub iterations: usize,
+    pub parse: Stats,
+    pub solve: Stats,
+}
+
+/// Parses `input` and solves `part` `iterations` times, timing each phase.
+pub async fn measure(
+    solution: &dyn Solution,
+    part: usize,
+    input: &str,
+    iterations: usize,
+) -> anyhow::Result<Measurement> {
+    anyhow::ensure!(iterations > 0, "At least one iteration is required.");
+
+    let mut parse = Vec::with_capacity(iterations);
+    let mut solve = Vec::with_capacity(iterations);
+    for _ in 0..iterations {
+        let start = Instant::now();
+        let parsed = solution.parse(input)?;
+        parse.push(start.elapsed());
+
+        let start = Instant::now();
+        solution.solve_parsed(part, &parsed).await?;
+        solve.push(start.elapsed());
+    }
+
+    Ok(Measurement {
+        day: solution.day(),
+        part,
+        iterations,
+        parse: Stats::new(parse),
+        solve: Stats::new(solve),
+    })
+}
+
+/// A saved set of measurements to compare later runs against.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Baseline {
+    pub measurements: Vec<Measurement>,
+}
+
+impl Baseline {
+    pub fn load(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Could not read baseline {}.", path.display()))?;
+        serde_json::from_str(&contents)
+            .with_context(|| format!("Could not parse baseline {}.", path.display()))
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        let path = path.as_ref();
+        let contents = serde_json::to_string_pretty(self)?;
+        std::fs::write(path, contents + "\n")
+            .with_context(|| format!("Could not write baseline {}.", path.display()))
+    }
+
+    pub fn get(&self, day: usize, part: usize) -> Option<&Measurement> {
+        self.measurements
+            .iter()
+            .find(|m| m.day == day && m.part == part)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    Parse,
+    Solve,
+}
+
+/// A phase whose median got slower than its baseline by more than allowed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Regression {
+    pub day: usize,
+    pub part: usize,
+    pub phase: Phase,
+    pub baseline: Duration,
+    pub current: Duration,
+}
+
+impl Regression {
+    /// How much slower the current median is, e.g. `0.25` for 25% slower.
+    pub fn slowdown(&self) -> f64 {
+        self.current.as_secs_f64() / self.baseline.as_secs_f64() - 1.0
+    }
+}
+
+/// Compares median timings of `current` against `baseline`, flagging every
+/// phase that is more than `threshold` (e.g. `0.1` for 10%) slower.
+pub fn regressions(baseline: &Baseline, current: &Baseline, threshold: f64) -> Vec<Regression> {
+    let mut regressions = vec![];
+    for measurement in current.measurements.iter() {
+        let Some(previous) = baseline.get(measurement.day, measurement.part) else {
+            continue;
+        };
+        for (phase, then, now) in [
+            (Phase::Parse, previous.parse, measurement.parse),
+            (Phase::Solve, previous.solve, measurement.solve),
+        ] {
+            if now.median_ns as f64 > then.median_ns as f64 * (1.0 + threshold) {
+                regressions.push(Regression {
+                    day: measurement.day,
+                    part: measurement.part,
+                    phase,
+                    baseline: then.median(),
+                    current: now.median(),
+                });
+            }
+        }
+    }
+    regressions
+}
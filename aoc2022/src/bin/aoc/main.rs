@@ -0,0 +1,265 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::Context as _;
+use structopt::StructOpt;
+use tokio::io::AsyncReadExt as _;
+
+use aoc2022::all::{self, Status};
+use aoc2022::bench::{self, Baseline};
+use aoc2022::{input, ParseError, SOLUTIONS};
+
+#[derive(StructOpt)]
+#[structopt(name = "aoc", about = "Advent of Code 2022 solutions.")]
+enum Args {
+    /// Lists every registered day and its parts.
+    List,
+    /// Runs one or all parts of a day against one of its inputs.
+    Run {
+        day: usize,
+        /// Runs every part of the day when omitted.
+        #[structopt(long)]
+        part: Option<usize>,
+        /// The name of an input under `dayN/` in the input root, such as `sample1`.
+        #[structopt(long, default_value = "main")]
+        input: String,
+        /// Reads this file instead of a named input, or stdin if `-`.
+        #[structopt(long, conflicts_with = "input")]
+        file: Option<PathBuf>,
+        /// The directory holding a `dayN/` directory of inputs for each day.
+        #[structopt(long, env = "AOC_INPUT_ROOT")]
+        input_root: Option<PathBuf>,
+    },
+    /// Runs every part of every day against every input at once and
+    /// summarizes the results. Use `bench` for meaningful timings.
+    All {
+        /// The directory holding a `dayN/` directory of inputs for each day.
+        #[structopt(long, env = "AOC_INPUT_ROOT")]
+        input_root: Option<PathBuf>,
+    },
+    /// Times parsing and solving of each part against its day's main input.
+    Bench {
+        /// Benchmarks every registered day when omitted.
+        #[structopt(long)]
+        day: Option<usize>,
+        /// Benchmarks every part of each day when omitted.
+        #[structopt(long)]
+        part: Option<usize>,
+        /// How many times to parse and solve each part.
+        #[structopt(long, default_value = "10")]
+        iterations: usize,
+        /// The directory holding a `dayN/` directory of inputs for each day.
+        #[structopt(long, env = "AOC_INPUT_ROOT")]
+        input_root: Option<PathBuf>,
+        /// Saves the measurements as a JSON baseline.
+        #[structopt(long)]
+        save: Option<PathBuf>,
+        /// Compares the measurements against a previously saved baseline.
+        #[structopt(long)]
+        baseline: Option<PathBuf>,
+        /// How much slower (in percent) a median may get before it counts as a regression.
+        #[structopt(long, default_value = "10")]
+        threshold: f64,
+    },
+}
+
+/// Reads the puzzle input from `path`, or from stdin if `path` is `-`.
+async fn read_input(path: &Path) -> anyhow::Result<String> {
+    if path == Path::new("-") {
+        let mut input = String::new();
+        tokio::io::stdin()
+            .read_to_string(&mut input)
+            .await
+            .context("Could not read stdin.")?;
+        Ok(input)
+    } else {
+        tokio::fs::read_to_string(path)
+            .await
+            .with_context(|| format!("Could not read {}.", path.display()))
+    }
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    match Args::from_args() {
+        Args::List => {
+            for solution in SOLUTIONS {
+                println!("Day {}: {}", solution.day(), solution.title());
+                for (i, name) in solution.parts().iter().enumerate() {
+                    println!("  Part {}: {}", i + 1, name);
+                }
+            }
+        }
+        Args::Run {
+            day,
+            part,
+            input,
+            file,
+            input_root,
+        } => {
+            let solution = aoc2022::solution::get(day)
+                .ok_or_else(|| anyhow::anyhow!("There is no solution for day {}.", day))?;
+            let parts = match part {
+                Some(part) if (1..=solution.parts().len()).contains(&part) => vec![part],
+                Some(part) => return Err(aoc2022::solution::no_such_part(solution, part)),
+                None => (1..=solution.parts().len()).collect(),
+            };
+
+            let file_path = match file {
+                Some(file) => file,
+                None => {
+                    let input_root =
+                        input_root.unwrap_or_else(|| PathBuf::from(input::DEFAULT_ROOT));
+                    input::resolve(&input_root, day, &input)?
+                }
+            };
+            let input = read_input(&file_path).await?;
+            let parsed = solution.parse(&input).map_err(|err| {
+                // Point parse errors at the file unless the input came from stdin.
+                let err = if file_path == Path::new("-") {
+                    err
+                } else {
+                    aoc2022::parse::in_file(err, &file_path)
+                };
+                match err.downcast_ref::<ParseError>() {
+                    Some(parse_error) => anyhow::anyhow!("{}", parse_error.render(&input)),
+                    None => err,
+                }
+            })?;
+            let mut failures = 0;
+            for &part in parts.iter() {
+                match solution.solve_parsed(part, &parsed).await {
+                    Ok(answer) if answer.is_multiline() => println!("Part {}:\n{}", part, answer),
+                    Ok(answer) => println!("Part {}: {}", part, answer),
+                    Err(err) => {
+                        failures += 1;
+                        println!("Part {}: {:?}", part, err);
+                    }
+                }
+            }
+            anyhow::ensure!(
+                failures == 0,
+                "{} of {} part(s) failed.",
+                failures,
+                parts.len(),
+            );
+        }
+        Args::All { input_root } => {
+            let input_root = input_root.unwrap_or_else(|| PathBuf::from(input::DEFAULT_ROOT));
+            let runs = all::run_all(&input_root).await;
+
+            println!(
+                "{:>3} {:>4} {:<10} {:<20} {:>10}  Status",
+                "Day", "Part", "Input", "Answer", "Time",
+            );
+            for run in runs.iter() {
+                let answer = match &run.result {
+                    Ok(answer) if answer.is_multiline() => "(image)".to_string(),
+                    Ok(answer) => answer.to_string(),
+                    Err(_) => "-".to_string(),
+                };
+                let status = run.status();
+                let detail = match (&run.result, &run.expected) {
+                    (Err(err), _) => format!(": {:#}", err).replace('\n', " "),
+                    (Ok(_), Some(expected)) if status == Status::Wrong => {
+                        format!(" (expected {})", expected).replace('\n', " ")
+                    }
+                    _ => String::new(),
+                };
+                println!(
+                    "{:>3} {:>4} {:<10} {:<20} {:>10.2?}  {}{}",
+                    run.day, run.part, run.input, answer, run.elapsed, status, detail,
+                );
+            }
+
+            let failures = runs
+                .iter()
+                .filter(|run| matches!(run.status(), Status::Wrong | Status::Failed))
+                .count();
+            anyhow::ensure!(
+                failures == 0,
+                "{} of {} run(s) failed.",
+                failures,
+                runs.len(),
+            );
+        }
+        Args::Bench {
+            day,
+            part,
+            iterations,
+            input_root,
+            save,
+            baseline,
+            threshold,
+        } => {
+            if let Some(day) = day {
+                let solution = aoc2022::solution::get(day)
+                    .ok_or_else(|| anyhow::anyhow!("There is no solution for day {}.", day))?;
+                if let Some(part) = part.filter(|part| !(1..=solution.parts().len()).contains(part))
+                {
+                    return Err(aoc2022::solution::no_such_part(solution, part));
+                }
+            }
+            let input_root = input_root.unwrap_or_else(|| PathBuf::from(input::DEFAULT_ROOT));
+            // Load the baseline first so a bad path fails before the slow part.
+            let baseline = baseline.map(Baseline::load).transpose()?;
+
+            println!(
+                "{:>3} {:>4} | {:>10} {:>10} {:>10} | {:>10} {:>10} {:>10}",
+                "Day", "Part", "parse min", "median", "max", "solve min", "median", "max",
+            );
+            let mut current = Baseline::default();
+            for solution in SOLUTIONS
+                .iter()
+                .filter(|solution| day.is_none_or(|day| solution.day() == day))
+            {
+                let path = input::resolve(&input_root, solution.day(), input::DEFAULT_NAME)?;
+                let input = read_input(&path).await?;
+                for p in 1..=solution.parts().len() {
+                    if part.is_some_and(|part| part != p) {
+                        continue;
+                    }
+                    let m = bench::measure(*solution, p, &input, iterations).await?;
+                    println!(
+                        "{:>3} {:>4} | {:>10.2?} {:>10.2?} {:>10.2?} | {:>10.2?} {:>10.2?} {:>10.2?}",
+                        m.day,
+                        m.part,
+                        m.parse.min(),
+                        m.parse.median(),
+                        m.parse.max(),
+                        m.solve.min(),
+                        m.solve.median(),
+                        m.solve.max(),
+                    );
+                    current.measurements.push(m);
+                }
+            }
+
+            if let Some(path) = save {
+                current.save(&path)?;
+                println!("Saved baseline to {}.", path.display());
+            }
+
+            if let Some(baseline) = baseline {
+                let regressions = bench::regressions(&baseline, &current, threshold / 100.0);
+                for r in regressions.iter() {
+                    println!(
+                        "REGRESSION day {} part {} {:?}: {:.2?} -> {:.2?} ({:+.1}%)",
+                        r.day,
+                        r.part,
+                        r.phase,
+                        r.baseline,
+                        r.current,
+                        r.slowdown() * 100.0,
+                    );
+                }
+                anyhow::ensure!(
+                    regressions.is_empty(),
+                    "{} phase(s) regressed by more than {}%.",
+                    regressions.len(),
+                    threshold,
+                );
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,192 @@
+use std::collections::BTreeMap;
+
+use crate::parse::{self, ParseError};
+
+/// What an instruction does once its last cycle is over, given its arguments.
+/// By then the instruction pointer has already moved on to the next
+/// instruction, so jumps overwrite it.
+pub type Effect = fn(&mut State, &[isize]) -> anyhow::Result<()>;
+
+/// One entry in an [`InstructionSet`].
+#[derive(Debug, Clone, Copy)]
+pub struct Opcode {
+    pub name: &'static str,
+    /// How many integer arguments follow the name.
+    pub args: usize,
+    /// How many cycles it takes to complete, at least 1.
+    pub cycles: usize,
+    pub effect: Effect,
+}
+
+/// The opcodes a program may use, in the order they are listed in errors.
+#[derive(Debug, Clone, Default)]
+pub struct InstructionSet {
+    opcodes: Vec<Opcode>,
+}
+
+impl InstructionSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `opcode`, replacing any existing one of the same name.
+    ///
+    /// Panics if it takes no cycles.
+    pub fn with(mut self, opcode: Opcode) -> Self {
+        assert!(
+            opcode.cycles > 0,
+            "`{}` must take at least one cycle",
+            opcode.name
+        );
+        match self.opcodes.iter_mut().find(|op| op.name == opcode.name) {
+            Some(existing) => *existing = opcode,
+            None => self.opcodes.push(opcode),
+        }
+        self
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Opcode> {
+        self.opcodes.iter().find(|opcode| opcode.name == name)
+    }
+
+    /// Parses one instruction per line: an opcode's name, then its arguments,
+    /// separated by whitespace.
+    pub fn parse(&self, source: &str) -> Result<Vec<Instruction>, ParseError> {
+        source
+            .lines()
+            .map(|line| self.parse_line(source, line))
+            .collect()
+    }
+
+    fn parse_line(&self, source: &str, line: &str) -> Result<Instruction, ParseError> {
+        let mut words = line.split_whitespace();
+        let name = words.next().unwrap_or(line);
+        let args = words.collect::<Vec<_>>();
+        let opcode = self
+            .get(name)
+            .filter(|opcode| opcode.args == args.len())
+            .ok_or_else(|| ParseError::at(source, line, self.expected()))?;
+        Ok(Instruction {
+            opcode: *opcode,
+            args: args
+                .into_iter()
+                .map(|arg| parse::value(source, arg, "isize"))
+                .collect::<Result<_, _>>()?,
+        })
+    }
+
+    /// Every opcode's syntax, e.g. "`addx <isize>` or `noop`".
+    fn expected(&self) -> String {
+        let syntax = self
+            .opcodes
+            .iter()
+            .map(|opcode| {
+                let mut syntax = format!("`{}", opcode.name);
+                for _ in 0..opcode.args {
+                    syntax.push_str(" <isize>");
+                }
+                syntax + "`"
+            })
+            .collect::<Vec<_>>();
+        parse::one_of(&syntax)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Instruction {
+    pub opcode: Opcode,
+    pub args: Vec<isize>,
+}
+
+/// Everything instructions can change.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct State {
+    registers: BTreeMap<char, isize>,
+    /// The index of the instruction being run, or to run next.
+    pub ip: usize,
+}
+
+impl State {
+    /// Starts at the first instruction with the given registers.
+    pub fn new(registers: impl IntoIterator<Item = (char, isize)>) -> Self {
+        Self {
+            registers: registers.into_iter().collect(),
+            ip: 0,
+        }
+    }
+
+    /// The value of register `name`, which is 0 if it was never set.
+    pub fn register(&self, name: char) -> isize {
+        self.registers.get(&name).copied().unwrap_or(0)
+    }
+
+    pub fn set_register(&mut self, name: char, value: isize) {
+        self.registers.insert(name, value);
+    }
+}
+
+/// Runs a program one cycle at a time. It halts once the instruction pointer
+/// is past the last instruction.
+#[derive(Debug, Clone)]
+pub struct Cpu<'a> {
+    program: &'a [Instruction],
+    state: State,
+    /// How many cycles have run.
+    cycle: usize,
+    /// How many cycles the current instruction has had so far.
+    progress: usize,
+}
+
+impl<'a> Cpu<'a> {
+    pub fn new(program: &'a [Instruction], state: State) -> Self {
+        Self {
+            program,
+            state,
+            cycle: 0,
+            progress: 0,
+        }
+    }
+
+    pub fn state(&self) -> &State {
+        &self.state
+    }
+
+    /// How many cycles have run.
+    pub fn cycle(&self) -> usize {
+        self.cycle
+    }
+
+    pub fn halted(&self) -> bool {
+        self.state.ip >= self.program.len()
+    }
+
+    /// Runs a single cycle, calling `hook` with its number, from 1, and the
+    /// state during it. Returns `false`, without running anything, if the
+    /// program has halted.
+    pub fn tick(&mut self, hook: &mut impl FnMut(usize, &State)) -> anyhow::Result<bool> {
+        let Some(instruction) = self.program.get(self.state.ip) else {
+            return Ok(false);
+        };
+        self.cycle += 1;
+        hook(self.cycle, &self.state);
+
+        self.progress += 1;
+        if self.progress == instruction.opcode.cycles {
+            self.progress = 0;
+            self.state.ip += 1;
+            (instruction.opcode.effect)(&mut self.state, &instruction.args)?;
+        }
+        Ok(true)
+    }
+
+    /// Runs until the program halts or `cycles` cycles have run in all,
+    /// calling `hook` on every cycle like [`Cpu::tick`].
+    pub fn run(
+        &mut self,
+        cycles: usize,
+        mut hook: impl FnMut(usize, &State),
+    ) -> anyhow::Result<()> {
+        while self.cycle < cycles && self.tick(&mut hook)? {}
+        Ok(())
+    }
+}
@@ -1,39 +1,53 @@
+use futures::future::BoxFuture;
 use futures::TryFutureExt as _;
-use std::path::Path;
 
-fn calculate_elves<'a>(
-    contents: &'a str,
+use crate::answer::Answer;
+use crate::parse;
+use crate::solution::{self, Parsed, Solution};
+
+/// The calories of each item, grouped by the elf carrying it.
+#[derive(Debug, Clone)]
+pub struct Elves(Vec<Vec<usize>>);
+
+pub fn parse(input: &str) -> anyhow::Result<Elves> {
+    input
+        .split("\n\n")
+        .map(|group| {
+            group
+                .lines()
+                .map(|item| parse::value::<usize>(input, item, "usize"))
+                .collect()
+        })
+        .collect::<Result<_, _>>()
+        .map(Elves)
+        .map_err(Into::into)
+}
+
+fn calculate_elves(
+    elves: &Elves,
 ) -> impl Iterator<
     Item = futures::future::UnwrapOrElse<
         tokio::task::JoinHandle<usize>,
         impl FnOnce(tokio::task::JoinError) -> usize,
     >,
-> + 'a {
-    contents.split("\n\n").map(|group| {
-        let group = group.to_string();
-        tokio::spawn(async move {
-            group
-                .split("\n")
-                .map(|item| str::parse::<usize>(item).unwrap())
-                .sum::<usize>()
-        })
-        .unwrap_or_else(|_| 0usize)
+> + '_ {
+    elves.0.iter().map(|items| {
+        let items = items.clone();
+        tokio::spawn(async move { items.into_iter().sum::<usize>() }).unwrap_or_else(|_| 0usize)
     })
 }
 
-pub async fn part1(path: impl AsRef<Path>) -> anyhow::Result<usize> {
-    let contents = tokio::fs::read_to_string(path).await?;
-    let elves = calculate_elves(&contents);
+pub async fn part1(elves: &Elves) -> anyhow::Result<usize> {
+    let elves = calculate_elves(elves);
     futures::future::join_all(elves)
         .await
         .into_iter()
         .max()
-        .ok_or(anyhow::anyhow!("File had no contents!"))
+        .ok_or(anyhow::anyhow!("Input had no contents!"))
 }
 
-pub async fn part2(path: impl AsRef<Path>) -> anyhow::Result<usize> {
-    let contents = tokio::fs::read_to_string(path).await?;
-    let elves = calculate_elves(&contents);
+pub async fn part2(elves: &Elves) -> anyhow::Result<usize> {
+    let elves = calculate_elves(elves);
 
     let mut bundles = futures::future::join_all(elves).await;
     if bundles.len() < 3 {
@@ -41,6 +55,44 @@ pub async fn part2(path: impl AsRef<Path>) -> anyhow::Result<usize> {
     }
     bundles.sort_unstable();
     Ok(bundles[(bundles.len() - 3)..bundles.len()]
-        .into_iter()
+        .iter()
         .sum::<usize>())
 }
+
+pub struct Day1;
+
+impl Solution for Day1 {
+    fn day(&self) -> usize {
+        1
+    }
+
+    fn title(&self) -> &'static str {
+        "Calorie Counting"
+    }
+
+    fn parts(&self) -> &'static [&'static str] {
+        &[
+            "Most calories carried by one elf",
+            "Calories carried by the top three elves",
+        ]
+    }
+
+    fn parse(&self, input: &str) -> anyhow::Result<Parsed> {
+        Ok(Box::new(parse(input)?))
+    }
+
+    fn solve_parsed<'a>(
+        &'a self,
+        part: usize,
+        parsed: &'a Parsed,
+    ) -> BoxFuture<'a, anyhow::Result<Answer>> {
+        Box::pin(async move {
+            let elves = solution::downcast::<Elves>(parsed)?;
+            match part {
+                1 => part1(elves).await.map(Answer::from),
+                2 => part2(elves).await.map(Answer::from),
+                _ => Err(solution::no_such_part(self, part)),
+            }
+        })
+    }
+}
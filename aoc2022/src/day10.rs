@@ -1,152 +1,108 @@
-use std::path::Path;
-use std::str::FromStr;
+use futures::future::BoxFuture;
 
-use anyhow::Context;
+use crate::answer::Answer;
+use crate::cpu::{Cpu, Instruction, InstructionSet, Opcode, State};
+use crate::grid::Grid;
+use crate::solution::{self, Parsed, Solution};
 
-#[derive(Debug, Clone)]
-enum Instruction {
-    Add(isize),
-    Noop,
+/// The handheld device's instructions, which only ever change register `x`.
+pub fn instruction_set() -> InstructionSet {
+    InstructionSet::new()
+        .with(Opcode {
+            name: "addx",
+            args: 1,
+            cycles: 2,
+            effect: |state, args| {
+                state.set_register('x', state.register('x') + args[0]);
+                Ok(())
+            },
+        })
+        .with(Opcode {
+            name: "noop",
+            args: 0,
+            cycles: 1,
+            effect: |_, _| Ok(()),
+        })
 }
 
-impl FromStr for Instruction {
-    type Err = anyhow::Error;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if s.contains("addx") {
-            s.get(4..)
-                .ok_or_else(|| anyhow::anyhow!("Line was not at least 5 characters long!"))
-                .map(str::trim)
-                .and_then(|s| {
-                    isize::from_str(s)
-                        .with_context(|| format!("Could not parse {} into an isize.", s))
-                })
-                .map(Self::Add)
-        } else if s.contains("noop") {
-            Ok(Self::Noop)
-        } else {
-            Err(anyhow::anyhow!("{} was not `addx {{isize}}` or `noop`.", s))
-        }
-    }
+pub fn parse(input: &str) -> anyhow::Result<Vec<Instruction>> {
+    Ok(instruction_set().parse(input)?)
 }
 
-#[derive(Debug, Clone)]
-struct Signal<const N: usize> {
-    cycles: [isize; N],
+/// Runs the program for `cycles` cycles with `x` starting at 1, calling `hook`
+/// with each cycle's number and the value of `x` during it.
+fn run(
+    instructions: &[Instruction],
+    cycles: usize,
+    mut hook: impl FnMut(usize, isize),
+) -> anyhow::Result<()> {
+    let mut cpu = Cpu::new(instructions, State::new([('x', 1)]));
+    cpu.run(cycles, |cycle, state| hook(cycle, state.register('x')))?;
+    anyhow::ensure!(
+        cpu.cycle() == cycles,
+        "The program halted after {} cycles, before cycle {}",
+        cpu.cycle(),
+        cycles
+    );
+    Ok(())
 }
 
-impl<const N: usize> Signal<N> {
-    fn new(initial: isize, instructions: &[Instruction]) -> Self {
-        let mut cycles = [initial; N];
+pub fn part1(instructions: &[Instruction]) -> anyhow::Result<isize> {
+    // The signal strength during the 20th cycle and every 40 cycles after that.
+    let mut sum = 0;
+    run(instructions, 220, |cycle, x| {
+        if cycle % 40 == 20 {
+            sum += cycle as isize * x;
+        }
+    })?;
+    Ok(sum)
+}
 
-        let mut value = initial;
-        let mut cycle_i = 0;
-        let mut instr_i = 0;
-        let mut instr_stage = 0;
-        while cycle_i < N {
-            match &instructions[instr_i] {
-                Instruction::Add(add) => match instr_stage {
-                    0 => {
-                        cycles[cycle_i] = value;
+pub fn part2(instructions: &[Instruction]) -> anyhow::Result<String> {
+    let mut screen = Grid::new(40, 6, '.');
+    let width = screen.width();
+    // Each cycle draws the next pixel, which is lit if the 3-pixel sprite
+    // centred on `x` covers it.
+    run(instructions, 240, |cycle, x| {
+        let (row, col) = ((cycle - 1) / width, (cycle - 1) % width);
+        if (x - 1..=x + 1).contains(&(col as isize)) {
+            screen[(row, col)] = '#';
+        }
+    })?;
+    Ok(screen.to_string())
+}
 
-                        instr_stage = 1;
-                    }
-                    1 => {
-                        cycles[cycle_i] = value;
+pub struct Day10;
 
-                        value += add;
-                        instr_stage = 0;
-                        instr_i += 1;
-                    }
-                    _ => unreachable!(),
-                },
-                Instruction::Noop => {
-                    cycles[cycle_i] = value;
-                    instr_i += 1;
-                }
-            }
-            cycle_i += 1;
-        }
+impl Solution for Day10 {
+    fn day(&self) -> usize {
+        10
+    }
 
-        Self { cycles }
+    fn title(&self) -> &'static str {
+        "Cathode-Ray Tube"
     }
 
-    fn signal_strength(&self, i: usize) -> anyhow::Result<isize> {
-        if (i - 1) < N {
-            Ok(i as isize * self.cycles[i - 1])
-        } else {
-            Err(anyhow::anyhow!("{} index out of bounds (max {}).", i, N))
-        }
+    fn parts(&self) -> &'static [&'static str] {
+        &["Sum of signal strengths", "CRT image"]
     }
 
-    fn get(&self, i: usize) -> anyhow::Result<isize> {
-        if (i - 1) < N {
-            Ok(self.cycles[i - 1])
-        } else {
-            Err(anyhow::anyhow!("{} index out of bounds (max {}).", i, N))
-        }
+    fn parse(&self, input: &str) -> anyhow::Result<Parsed> {
+        Ok(Box::new(parse(input)?))
     }
-}
 
-pub async fn part1(path: impl AsRef<Path>) -> anyhow::Result<isize> {
-    let contents = tokio::fs::read_to_string(path).await?;
-    let instructions = contents
-        .lines()
-        .map(Instruction::from_str)
-        .collect::<Result<Vec<_>, _>>()?;
-    let signal = Signal::<220>::new(1, &instructions);
-    signal
-        .signal_strength(20)
-        .and_then(|i20| signal.signal_strength(60).map(move |i60| (i20, i60)))
-        .and_then(|(i20, i60)| {
-            signal
-                .signal_strength(100)
-                .map(move |i100| (i20, i60, i100))
-        })
-        .and_then(|(i20, i60, i100)| {
-            signal
-                .signal_strength(140)
-                .map(move |i140| (i20, i60, i100, i140))
-        })
-        .and_then(|(i20, i60, i100, i140)| {
-            signal
-                .signal_strength(180)
-                .map(move |i180| (i20, i60, i100, i140, i180))
-        })
-        .and_then(|(i20, i60, i100, i140, i180)| {
-            signal
-                .signal_strength(220)
-                .map(move |i220| (i20, i60, i100, i140, i180, i220))
+    fn solve_parsed<'a>(
+        &'a self,
+        part: usize,
+        parsed: &'a Parsed,
+    ) -> BoxFuture<'a, anyhow::Result<Answer>> {
+        Box::pin(async move {
+            let instructions = solution::downcast::<Vec<Instruction>>(parsed)?;
+            match part {
+                1 => part1(instructions).map(Answer::from),
+                2 => part2(instructions).map(Answer::image),
+                _ => Err(solution::no_such_part(self, part)),
+            }
         })
-        .map(|(i20, i60, i100, i140, i180, i220)| i20 + i60 + i100 + i140 + i180 + i220)
-}
-
-pub async fn part2(path: impl AsRef<Path>) -> anyhow::Result<String> {
-    let contents = tokio::fs::read_to_string(path).await?;
-    let instructions = contents
-        .lines()
-        .map(Instruction::from_str)
-        .collect::<Result<Vec<_>, _>>()?;
-    let signal = Signal::<240>::new(1, &instructions);
-
-    let mut output = String::with_capacity(240);
-    for i in 0..240 {
-        let value = signal.get(i + 1)?;
-        output.push(
-            if (value - 1) <= (i as isize % 40) && (i as isize % 40) <= (value + 1) {
-                '#'
-            } else {
-                '.'
-            },
-        );
     }
-    Ok(format!(
-        "{}\n{}\n{}\n{}\n{}\n{}\n",
-        &output[0..40],
-        &output[40..80],
-        &output[80..120],
-        &output[120..160],
-        &output[160..200],
-        &output[200..240],
-    ))
 }
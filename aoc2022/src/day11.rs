@@ -1,254 +1,939 @@
-use std::collections::VecDeque;
-use std::path::Path;
+use std::cmp::Reverse;
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::hash::Hash;
+use std::ops::{Div, Rem};
 use std::str::FromStr;
 
-use anyhow::Context;
+use futures::future::BoxFuture;
+use num_traits::{CheckedAdd, CheckedMul, CheckedSub, FromPrimitive, ToPrimitive, Zero};
 
-#[derive(Debug)]
+use crate::answer::Answer;
+use crate::parse::ParseError;
+use crate::solution::{self, Parsed, Solution};
+
+/// A binary operator in a monkey's operation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+}
+
+impl Op {
+    fn from_token(token: &str) -> Option<Self> {
+        match token {
+            "+" => Some(Self::Add),
+            "-" => Some(Self::Sub),
+            "*" => Some(Self::Mul),
+            "/" => Some(Self::Div),
+            "%" => Some(Self::Rem),
+            _ => None,
+        }
+    }
+
+    fn symbol(self) -> char {
+        match self {
+            Self::Add => '+',
+            Self::Sub => '-',
+            Self::Mul => '*',
+            Self::Div => '/',
+            Self::Rem => '%',
+        }
+    }
+
+    /// Whether `(a op b) mod m` can be worked out from `a mod m` and `b mod m`
+    /// with unsigned worry levels.
+    fn is_modular(self) -> bool {
+        self.non_modular_reason().is_none()
+    }
+
+    /// Why the operator can't be worked out on reduced worry levels, if it
+    /// can't. Subtraction would give the right remainder, but could fail on a
+    /// reduced level that is smaller than the real one.
+    fn non_modular_reason(self) -> Option<&'static str> {
+        match self {
+            Self::Add | Self::Mul => None,
+            Self::Sub => Some("a reduced level can go below zero when the real one doesn't"),
+            Self::Div | Self::Rem => Some(
+                "dividing a reduced level doesn't give the same remainder as dividing the real one",
+            ),
+        }
+    }
+
+    /// Applies the operator, failing rather than letting the result wrap.
+    fn apply<W: Worry>(self, lhs: &W, rhs: &W) -> anyhow::Result<W> {
+        let value = match self {
+            Self::Add => lhs.checked_add(rhs),
+            Self::Sub => lhs.checked_sub(rhs),
+            Self::Mul => lhs.checked_mul(rhs),
+            Self::Div | Self::Rem if rhs.is_zero() => {
+                anyhow::bail!("Division by zero in `{} {} 0`", lhs, self.symbol())
+            }
+            Self::Div => Some(lhs.clone() / rhs.clone()),
+            Self::Rem => Some(lhs.clone() % rhs.clone()),
+        };
+        value.ok_or_else(|| {
+            let problem = match self {
+                Self::Sub => "goes below zero".to_string(),
+                _ => format!("overflows a `{}`", std::any::type_name::<W>()),
+            };
+            anyhow::anyhow!(
+                "Worry level `{} {} {}` {}",
+                lhs,
+                self.symbol(),
+                rhs,
+                problem
+            )
+        })
+    }
+
+    /// Applies the operator to `lhs` and `rhs`, both less than `modulus`.
+    fn apply_modulo(self, lhs: usize, rhs: usize, modulus: usize) -> anyhow::Result<usize> {
+        let (lhs, rhs, modulus) = (lhs as u128, rhs as u128, modulus as u128);
+        let value = match self {
+            Self::Add => lhs + rhs,
+            Self::Mul => lhs * rhs,
+            Self::Sub | Self::Div | Self::Rem => {
+                anyhow::bail!(
+                    "`{}` cannot be worked out modulo {}",
+                    self.symbol(),
+                    modulus
+                )
+            }
+        };
+        Ok((value % modulus) as usize)
+    }
+}
+
+/// The right-hand side of a monkey's `Operation: new = ...` line.
+#[derive(Debug, Clone, PartialEq, Eq)]
 enum Expr {
-    Add(usize),
-    Mul(usize),
-    Square,
+    Old,
+    Number(usize),
+    Binary(Box<Expr>, Op, Box<Expr>),
 }
 
 impl Expr {
-    fn eval(&self, val: usize) -> usize {
+    /// Evaluates the expression with `old` as the current worry level.
+    fn eval<W: Worry>(&self, old: &W) -> anyhow::Result<W> {
         match self {
-            Self::Add(other) => val.wrapping_add(*other),
-            Self::Mul(other) => val.wrapping_mul(*other),
-            Self::Square => val.wrapping_pow(2),
+            Self::Old => Ok(old.clone()),
+            Self::Number(n) => Ok(worry(*n)),
+            Self::Binary(lhs, op, rhs) => op.apply(&lhs.eval(old)?, &rhs.eval(old)?),
         }
     }
+
+    /// Evaluates the expression modulo `modulus`, reducing every intermediate
+    /// value so nothing can overflow. Fails on any operator that isn't
+    /// [`Op::is_modular`].
+    fn eval_modulo<W: Worry>(&self, old: &W, modulus: usize) -> anyhow::Result<usize> {
+        match self {
+            Self::Old => Ok(reduce(old, modulus)),
+            Self::Number(n) => Ok(n % modulus),
+            Self::Binary(lhs, op, rhs) => op.apply_modulo(
+                lhs.eval_modulo(old, modulus)?,
+                rhs.eval_modulo(old, modulus)?,
+                modulus,
+            ),
+        }
+    }
+
+    /// The first operator, from left to right, that isn't [`Op::is_modular`].
+    fn non_modular_op(&self) -> Option<Op> {
+        match self {
+            Self::Old | Self::Number(_) => None,
+            Self::Binary(lhs, op, rhs) => lhs
+                .non_modular_op()
+                .or_else(|| (!op.is_modular()).then_some(*op))
+                .or_else(|| rhs.non_modular_op()),
+        }
+    }
+}
+
+/// Splits an operation into numbers, words and single-character symbols.
+struct Tokens<'a> {
+    source: &'a str,
+    rest: &'a str,
 }
 
-#[derive(Debug)]
+impl<'a> Tokens<'a> {
+    /// The next token, which is empty at the end of the line.
+    fn peek(&self) -> &'a str {
+        let rest = self.rest.trim_start();
+        let run = |pred: fn(char) -> bool| rest.find(|c| !pred(c)).unwrap_or(rest.len());
+        let len = match rest.chars().next() {
+            None => 0,
+            Some(c) if c.is_ascii_digit() => run(|c| c.is_ascii_digit()),
+            Some(c) if c.is_alphabetic() => run(char::is_alphanumeric),
+            Some(c) => c.len_utf8(),
+        };
+        &rest[..len]
+    }
+
+    fn next(&mut self) -> &'a str {
+        let token = self.peek();
+        self.rest = &self.rest.trim_start()[token.len()..];
+        token
+    }
+
+    /// The next token if it is one of `ops`.
+    fn next_op(&mut self, ops: &[Op]) -> Option<Op> {
+        let op = Op::from_token(self.peek()).filter(|op| ops.contains(op))?;
+        self.next();
+        Some(op)
+    }
+
+    /// `sum := product (("+" | "-") product)*`
+    fn sum(&mut self) -> Result<Expr, ParseError> {
+        let mut expr = self.product()?;
+        while let Some(op) = self.next_op(&[Op::Add, Op::Sub]) {
+            expr = Expr::Binary(Box::new(expr), op, Box::new(self.product()?));
+        }
+        Ok(expr)
+    }
+
+    /// `product := atom (("*" | "/" | "%") atom)*`
+    fn product(&mut self) -> Result<Expr, ParseError> {
+        let mut expr = self.atom()?;
+        while let Some(op) = self.next_op(&[Op::Mul, Op::Div, Op::Rem]) {
+            expr = Expr::Binary(Box::new(expr), op, Box::new(self.atom()?));
+        }
+        Ok(expr)
+    }
+
+    /// `atom := number | "old" | "(" sum ")"`
+    fn atom(&mut self) -> Result<Expr, ParseError> {
+        let token = self.next();
+        match token {
+            "old" => Ok(Expr::Old),
+            "(" => {
+                let expr = self.sum()?;
+                let close = self.next();
+                if close != ")" {
+                    return Err(ParseError::at(self.source, close, "`)` or an operator"));
+                }
+                Ok(expr)
+            }
+            _ if token.starts_with(|c: char| c.is_ascii_digit()) => usize::from_str(token)
+                .map(Expr::Number)
+                .map_err(|_| ParseError::at(self.source, token, "a number that fits in a `usize`")),
+            _ => Err(ParseError::at(self.source, token, "a number, `old` or `(`")),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
 struct Monkey {
     items: VecDeque<usize>,
     operation: Expr,
     test_divisible_by: usize,
     on_success_monkey_ind: usize,
     on_failure_monkey_ind: usize,
-    inspection_count: usize,
 }
 
-#[derive(Debug)]
-struct Monkeys {
+#[derive(Debug, Clone)]
+pub struct Monkeys {
     monkeys: Vec<Monkey>,
 }
 
-fn parse_monkey_line_0(s: &str) -> anyhow::Result<usize> {
-    let c = s
-        .chars()
-        .nth(7)
-        .ok_or_else(|| anyhow::anyhow!("Line did not have at least 8 chars."))?;
-    c.to_digit(10)
-        .map(|i| i as usize)
-        .ok_or_else(|| anyhow::anyhow!("Char could not be parsed into a digit."))
-}
-
-fn parse_monkey_line_1(s: &str) -> anyhow::Result<VecDeque<usize>> {
-    Ok(s.get(17..)
-        .ok_or_else(|| anyhow::anyhow!("Line did not have at least 18 characters."))?
-        .split(",")
-        .map(str::trim)
-        .map(usize::from_str)
-        .collect::<Result<VecDeque<_>, _>>()
-        .context("Could not convert str to usize.")?)
-}
-
-fn parse_monkey_line_2(s: &str) -> anyhow::Result<Expr> {
-    let op = s
-        .chars()
-        .nth(23)
-        .ok_or_else(|| anyhow::anyhow!("Line did have at least 24 characters!"))?;
-    let argument = s
-        .get(24..)
-        .ok_or_else(|| anyhow::anyhow!("Line did not have at least 25 characters!"))
-        .map(str::trim)?;
-    match (op, argument) {
-        ('+', arg) => arg.parse::<usize>().map(Expr::Add).with_context(|| {
-            format!(
-                "Argument to addition operation could not be parsed to a usize: {}.",
-                arg,
-            )
-        }),
-        ('*', "old") => Ok(Expr::Square),
-        ('*', arg) => arg.parse::<usize>().map(Expr::Mul).with_context(|| {
-            format!(
-                "Argument to multiplication operation could not be parsed to a usize: {}.",
-                arg,
-            )
-        }),
-        _ => anyhow::bail!("Unrecognized operator {}!", op),
+/// Strips the words of `keyword` from the start of `rest`, allowing any
+/// whitespace before and between them.
+fn keyword<'a>(rest: &'a str, keyword: &str) -> Option<&'a str> {
+    keyword
+        .split_whitespace()
+        .try_fold(rest, |rest, word| rest.trim_start().strip_prefix(word))
+}
+
+/// Like [`keyword`], but fails pointing at whatever is there instead.
+fn expect_keyword<'a>(source: &str, rest: &'a str, expected: &str) -> Result<&'a str, ParseError> {
+    keyword(rest, expected)
+        .ok_or_else(|| ParseError::at(source, rest.trim(), format!("`{}`", expected)))
+}
+
+fn number(source: &str, text: &str, expected: &str) -> Result<usize, ParseError> {
+    let text = text.trim();
+    usize::from_str(text).map_err(|_| ParseError::at(source, text, expected))
+}
+
+fn parse_items(source: &str, rest: &str) -> Result<VecDeque<usize>, ParseError> {
+    if rest.trim().is_empty() {
+        return Ok(VecDeque::new());
     }
+    rest.split(',')
+        .map(|item| number(source, item, "a worry level"))
+        .collect()
 }
 
-fn parse_monkey_line_3(s: &str) -> anyhow::Result<usize> {
-    s.get(21..)
-        .ok_or_else(|| anyhow::anyhow!("Line did not have 22 characters."))
-        .and_then(|s| {
-            usize::from_str(s).with_context(|| format!("Could not parse {} into usize.", s))
-        })
+fn parse_operation(source: &str, rest: &str) -> Result<Expr, ParseError> {
+    let mut tokens = Tokens {
+        source,
+        rest: expect_keyword(source, rest, "new =")?,
+    };
+    let expr = tokens.sum()?;
+    let rest = tokens.peek();
+    if !rest.is_empty() {
+        return Err(ParseError::at(source, rest, "an operator"));
+    }
+    Ok(expr)
 }
 
-fn parse_monkey_line_4(s: &str) -> anyhow::Result<usize> {
-    s.chars()
-        .nth(29)
-        .ok_or_else(|| anyhow::anyhow!("Line did not have 30 characters."))
-        .and_then(|c| {
-            c.to_digit(10)
-                .with_context(|| format!("Could not parse {} into a base-10 digit.", c))
-        })
-        .map(|i| i as usize)
+fn parse_test(source: &str, rest: &str) -> Result<usize, ParseError> {
+    let divisor = expect_keyword(source, rest, "divisible by")?.trim();
+    match number(source, divisor, "a divisor")? {
+        0 => Err(ParseError::at(source, divisor, "a divisor other than 0")),
+        divisor => Ok(divisor),
+    }
 }
 
-fn parse_monkey_line_5(s: &str) -> anyhow::Result<usize> {
-    s.chars()
-        .nth(30)
-        .ok_or_else(|| anyhow::anyhow!("Line did not have 31 characters."))
-        .and_then(|c| {
-            c.to_digit(10)
-                .with_context(|| format!("Could not parse {} into a base-10 digit.", c))
-        })
-        .map(|i| i as usize)
+/// The monkey thrown to, with its text for errors.
+fn parse_target<'a>(source: &str, rest: &'a str) -> Result<(usize, &'a str), ParseError> {
+    let target = expect_keyword(source, rest, "throw to monkey")?.trim();
+    Ok((number(source, target, "a monkey number")?, target))
+}
+
+/// The lines that describe a monkey after its `Monkey <n>:` header, which may
+/// come in any order.
+const ATTRIBUTES: [&str; 5] = [
+    "Starting items:",
+    "Operation:",
+    "Test:",
+    "If true:",
+    "If false:",
+];
+
+/// A monkey whose throw targets have not yet been checked against the others.
+struct Partial<'a> {
+    monkey: Monkey,
+    /// The text of the success and failure targets, for errors.
+    targets: [&'a str; 2],
+}
+
+fn parse_monkey<'a>(
+    source: &str,
+    index: usize,
+    lines: &[&'a str],
+) -> Result<Partial<'a>, ParseError> {
+    let header = lines[0];
+    let id = expect_keyword(source, header, "Monkey")?.trim();
+    let id = id
+        .strip_suffix(':')
+        .ok_or_else(|| ParseError::after(source, id, "`:`"))?
+        .trim_end();
+    if number(source, id, "a monkey number")? != index {
+        return Err(ParseError::at(source, id, format!("monkey {}", index)));
+    }
+
+    let mut items = None;
+    let mut operation = None;
+    let mut test = None;
+    let mut on_success = None;
+    let mut on_failure = None;
+    for &line in &lines[1..] {
+        let (i, rest) = ATTRIBUTES
+            .iter()
+            .enumerate()
+            .find_map(|(i, attribute)| Some((i, keyword(line, attribute)?)))
+            .ok_or_else(|| {
+                ParseError::at(
+                    source,
+                    line.trim(),
+                    "`Starting items:`, `Operation:`, `Test:`, `If true:` or `If false:`",
+                )
+            })?;
+        let duplicate = match i {
+            0 => items.replace(parse_items(source, rest)?).is_some(),
+            1 => operation.replace(parse_operation(source, rest)?).is_some(),
+            2 => test.replace(parse_test(source, rest)?).is_some(),
+            3 => on_success.replace(parse_target(source, rest)?).is_some(),
+            _ => on_failure.replace(parse_target(source, rest)?).is_some(),
+        };
+        if duplicate {
+            return Err(ParseError::at(
+                source,
+                line.trim(),
+                format!("only one `{}` line per monkey", ATTRIBUTES[i]),
+            ));
+        }
+    }
+
+    let missing = |i: usize| {
+        ParseError::after(
+            source,
+            lines.last().unwrap(),
+            format!("a `{}` line", ATTRIBUTES[i]),
+        )
+    };
+    let (on_success, on_failure) = (
+        on_success.ok_or_else(|| missing(3))?,
+        on_failure.ok_or_else(|| missing(4))?,
+    );
+    Ok(Partial {
+        monkey: Monkey {
+            items: items.ok_or_else(|| missing(0))?,
+            operation: operation.ok_or_else(|| missing(1))?,
+            test_divisible_by: test.ok_or_else(|| missing(2))?,
+            on_success_monkey_ind: on_success.0,
+            on_failure_monkey_ind: on_failure.0,
+        },
+        targets: [on_success.1, on_failure.1],
+    })
 }
 
 impl FromStr for Monkeys {
-    type Err = anyhow::Error;
+    type Err = ParseError;
 
+    /// Each monkey starts at a `Monkey <n>:` line, with monkeys numbered from
+    /// 0 in order. Blank lines, indentation and line endings don't matter.
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chunks: Vec<Vec<&str>> = vec![];
+        for line in s.lines().filter(|line| !line.trim().is_empty()) {
+            match chunks.last_mut() {
+                Some(chunk) if keyword(line, "Monkey").is_none() => chunk.push(line),
+                _ => chunks.push(vec![line]),
+            }
+        }
+        if chunks.is_empty() {
+            return Err(ParseError::after(s, s, "`Monkey 0:`"));
+        }
+
+        let partials = chunks
+            .iter()
+            .enumerate()
+            .map(|(i, lines)| parse_monkey(s, i, lines))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let count = partials.len();
+        for Partial { monkey, targets } in partials.iter() {
+            let ids = [monkey.on_success_monkey_ind, monkey.on_failure_monkey_ind];
+            if let Some(i) = (0..2).find(|i| ids[*i] >= count) {
+                return Err(ParseError::at(
+                    s,
+                    targets[i],
+                    format!("a monkey from 0 to {}", count - 1),
+                ));
+            }
+        }
+
         Ok(Monkeys {
-            monkeys: s
-                .split("\n\n")
-                .enumerate()
-                .map(|(i, item)| -> anyhow::Result<Monkey> {
-                    let lines = item.trim().lines().collect::<Vec<_>>();
-
-                    let index =
-                        parse_monkey_line_0(item).context("Could not parse monkey line 0!")?;
-                    anyhow::ensure!(
-                        index == i,
-                        "Monkey parsing index is not what is expected! {} vs {}",
-                        index,
-                        i,
-                    );
-
-                    Ok(Monkey {
-                        items: parse_monkey_line_1(lines[1])
-                            .context("Could not parse monkey line 1!")?,
-                        operation: parse_monkey_line_2(lines[2])
-                            .context("Could not parse monkey line 2!")?,
-                        test_divisible_by: parse_monkey_line_3(lines[3])
-                            .context("Could not parse monkey line 3!")?,
-                        on_success_monkey_ind: parse_monkey_line_4(lines[4])
-                            .context("Could not parse monkey line 4!")?,
-                        on_failure_monkey_ind: parse_monkey_line_5(lines[5])
-                            .context("Could not parse monkey line 5!")?,
-                        inspection_count: 0,
-                    })
-                })
-                .collect::<Result<Vec<_>, _>>()?,
+            monkeys: partials.into_iter().map(|partial| partial.monkey).collect(),
         })
     }
 }
 
+/// An unsigned integer type to keep worry levels in: `usize`, `u128` or
+/// [`BigUint`](num_bigint::BigUint), which never overflows.
+pub trait Worry:
+    Clone
+    + Eq
+    + Hash
+    + fmt::Display
+    + FromPrimitive
+    + ToPrimitive
+    + Zero
+    + CheckedAdd
+    + CheckedSub
+    + CheckedMul
+    + Div<Output = Self>
+    + Rem<Output = Self>
+{
+}
+
+impl<W> Worry for W where
+    W: Clone
+        + Eq
+        + Hash
+        + fmt::Display
+        + FromPrimitive
+        + ToPrimitive
+        + Zero
+        + CheckedAdd
+        + CheckedSub
+        + CheckedMul
+        + Div<Output = Self>
+        + Rem<Output = Self>
+{
+}
+
+fn worry<W: Worry>(n: usize) -> W {
+    W::from_usize(n).expect("every worry type holds any usize")
+}
+
+/// `value` modulo `modulus`, which always fits back in a `usize`.
+fn reduce<W: Worry>(value: &W, modulus: usize) -> usize {
+    (value.clone() % worry(modulus))
+        .to_usize()
+        .expect("a remainder is less than its usize modulus")
+}
+
+/// How a monkey's worry level drops after it inspects an item, before it
+/// decides where to throw it.
+pub trait Relief<W = usize> {
+    fn relieve(&self, worry: W) -> W;
+
+    /// If relief reduces worry modulo some number, that number. Operations
+    /// that allow it are then worked out modulo it, which gives the same
+    /// result without the intermediate values growing.
+    fn modulus(&self) -> Option<usize> {
+        None
+    }
+}
+
+/// Integer division by a constant, like the puzzle's "divided by three".
+#[derive(Debug, Clone, Copy)]
+pub struct DivideBy(pub usize);
+
+impl<W: Worry> Relief<W> for DivideBy {
+    fn relieve(&self, value: W) -> W {
+        value / worry(self.0)
+    }
+}
+
+/// Reduction modulo a constant, which leaves every test's outcome unchanged
+/// if it is a multiple of every divisor, such as [`Monkeys::lcm`].
+#[derive(Debug, Clone, Copy)]
+pub struct Modulo(pub usize);
+
+impl<W: Worry> Relief<W> for Modulo {
+    fn relieve(&self, value: W) -> W {
+        value % worry(self.0)
+    }
+
+    fn modulus(&self) -> Option<usize> {
+        Some(self.0)
+    }
+}
+
+/// No relief at all, so worry levels only ever grow.
+#[derive(Debug, Clone, Copy)]
+pub struct NoRelief;
+
+impl<W> Relief<W> for NoRelief {
+    fn relieve(&self, worry: W) -> W {
+        worry
+    }
+}
+
+impl<W, F: Fn(W) -> W> Relief<W> for F {
+    fn relieve(&self, worry: W) -> W {
+        self(worry)
+    }
+}
+
+fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// The items every monkey holds, and how many each has inspected so far.
+/// Items are numbered in the order they are listed in the notes.
+#[derive(Debug, Clone)]
+struct State<W> {
+    items: Vec<VecDeque<(usize, W)>>,
+    inspection_counts: Vec<usize>,
+}
+
 impl Monkeys {
-    fn lcm(&self) -> usize {
-        // N.B.: This works as lcm because all of the divisors are prime.
-        self.monkeys
-            .iter()
-            .map(
-                |Monkey {
-                     test_divisible_by, ..
-                 }| test_divisible_by,
-            )
-            .product()
-    }
-
-    fn simulate<const PART: usize>(&mut self) {
-        let lcm = self.lcm();
-
-        let mut temp_items = vec![VecDeque::<usize>::new(); self.monkeys.len()];
-
-        // Simulate the current round by applying the monkey operations and tests in order.
-        for i in 0..(self.monkeys.len()) {
-            let monkey = &mut self.monkeys[i];
-            monkey.items.extend(temp_items[i].drain(..));
-            for item in monkey.items.drain(..) {
-                // Increment expection count.
-                monkey.inspection_count += 1;
-
-                // Apply operation.
-                let mut new_val = monkey.operation.eval(item);
-                if PART == 1 {
-                    // Divide by 3 for monkey getting bored. This is integer division.
-                    new_val /= 3;
-                } else {
-                    // Divide by LCM of all Monkey divisors. Oh, because they
-                    // are all prime and this can't change the answer since each
-                    // test is a prime modulo.
-                    new_val %= lcm;
-                }
+    /// The least common multiple of every monkey's test divisor.
+    pub fn lcm(&self) -> anyhow::Result<usize> {
+        self.monkeys.iter().try_fold(1, |lcm: usize, monkey| {
+            let divisor = monkey.test_divisible_by;
+            (lcm / gcd(lcm, divisor))
+                .checked_mul(divisor)
+                .ok_or_else(|| anyhow::anyhow!("The LCM of the test divisors overflows a usize"))
+        })
+    }
 
-                // Run the test to see the next monkey.
-                if new_val % monkey.test_divisible_by == 0 {
-                    temp_items[monkey.on_success_monkey_ind].push_back(new_val);
-                } else {
-                    temp_items[monkey.on_failure_monkey_ind].push_back(new_val);
-                }
+    /// The starting items, before anything has been inspected.
+    fn start<W: Worry>(&self) -> State<W> {
+        let mut ids = 0..;
+        State {
+            items: self
+                .monkeys
+                .iter()
+                .map(|monkey| {
+                    monkey
+                        .items
+                        .iter()
+                        .map(|item| (ids.next().unwrap(), worry(*item)))
+                        .collect()
+                })
+                .collect(),
+            inspection_counts: vec![0; self.monkeys.len()],
+        }
+    }
+
+    /// Fails if `relief` has a modulus and some monkey's operation can't be
+    /// worked out modulo it, since the reduced worry levels would then give
+    /// different results from the real ones.
+    fn check_modulus<W>(&self, relief: &dyn Relief<W>) -> anyhow::Result<()> {
+        let Some(modulus) = relief.modulus() else {
+            return Ok(());
+        };
+        for (i, monkey) in self.monkeys.iter().enumerate() {
+            if let Some(op) = monkey.operation.non_modular_op() {
+                anyhow::bail!(
+                    "Monkey {}'s operation uses `{}`, which can't be worked out on worry levels reduced modulo {}: {}",
+                    i,
+                    op.symbol(),
+                    modulus,
+                    op.non_modular_reason().unwrap_or_default(),
+                );
             }
         }
+        Ok(())
+    }
 
-        // Add the remaining temp_items to the monkey queues for the next round.
+    /// Monkey `i` inspects an item, returning its new worry level and which
+    /// monkey it is thrown to.
+    fn inspect<W: Worry>(
+        &self,
+        i: usize,
+        item: &W,
+        relief: &dyn Relief<W>,
+    ) -> anyhow::Result<(usize, W)> {
+        let monkey = &self.monkeys[i];
+
+        // Apply operation, then relief. With a modulus, every operation has
+        // already been checked to work out the same on reduced worry levels.
+        let new_val = match relief.modulus() {
+            Some(modulus) => worry(monkey.operation.eval_modulo(item, modulus)?),
+            None => monkey.operation.eval(item)?,
+        };
+        let new_val = relief.relieve(new_val);
+
+        // Run the test to see the next monkey.
+        let target = if reduce(&new_val, monkey.test_divisible_by) == 0 {
+            monkey.on_success_monkey_ind
+        } else {
+            monkey.on_failure_monkey_ind
+        };
+        Ok((target, new_val))
+    }
+
+    /// Plays one round, where each monkey in turn inspects and throws all of
+    /// its items. `on_throw` sees the number of each item thrown, who threw it
+    /// to whom, and its new worry level.
+    fn simulate<W: Worry>(
+        &self,
+        state: &mut State<W>,
+        relief: &dyn Relief<W>,
+        mut on_throw: impl FnMut(usize, usize, usize, &W),
+    ) -> anyhow::Result<()> {
         for i in 0..self.monkeys.len() {
-            self.monkeys[i].items.extend(temp_items[i].drain(..));
+            for (id, item) in std::mem::take(&mut state.items[i]) {
+                state.inspection_counts[i] += 1;
+                let (target, new_val) = self.inspect(i, &item, relief)?;
+                on_throw(id, i, target, &new_val);
+                state.items[target].push_back((id, new_val));
+            }
         }
+        Ok(())
     }
 
-    fn print_monkey_inspection_counts(&self) {
-        println!(
-            "{:#?}",
-            self.monkeys
-                .iter()
-                .map(
-                    |Monkey {
-                         inspection_count, ..
-                     }| *inspection_count,
-                )
-                .collect::<Vec<_>>()
-        )
+    /// Follows one item through the rest of a round, starting with monkey
+    /// `i`. Items thrown to a later monkey are inspected again this round, so
+    /// it ends up with the first monkey that isn't later than the last. Each
+    /// inspection is added to `inspection_counts`.
+    fn follow<W: Worry>(
+        &self,
+        mut i: usize,
+        mut item: W,
+        relief: &dyn Relief<W>,
+        inspection_counts: &mut [usize],
+    ) -> anyhow::Result<(usize, W)> {
+        loop {
+            inspection_counts[i] += 1;
+            let (target, new_val) = self.inspect(i, &item, relief)?;
+            if target <= i {
+                return Ok((target, new_val));
+            }
+            (i, item) = (target, new_val);
+        }
     }
 
-    fn monkey_business_level(&self) -> usize {
-        let (first, second) = self
-            .monkeys
-            .iter()
-            .map(
-                |Monkey {
-                     inspection_count, ..
-                 }| *inspection_count,
-            )
-            .fold((0, 0), |(first, second), count| {
-                if count > first {
-                    (count, first)
-                } else if count > second {
-                    (first, count)
-                } else {
-                    (first, second)
+    /// Adds one item's inspections over `rounds` rounds to
+    /// `inspection_counts`. Items never affect each other, and where an item
+    /// is and how worrying it is at the start of a round decides every round
+    /// after, so as soon as that repeats the rest is extrapolated.
+    fn count_item_inspections<W: Worry>(
+        &self,
+        (mut i, mut item): (usize, W),
+        rounds: usize,
+        relief: &dyn Relief<W>,
+        inspection_counts: &mut [usize],
+    ) -> anyhow::Result<()> {
+        let n = self.monkeys.len();
+        let mut seen = HashMap::new();
+        let mut counts = vec![0; n];
+        // The counts after each round so far, flattened.
+        let mut history = counts.clone();
+        for round in 0..rounds {
+            if let Some(&start) = seen.get(&(i, item.clone())) {
+                let period = round - start;
+                let (cycles, rest) = ((rounds - round) / period, (rounds - round) % period);
+                let after = |round: usize, k: usize| history[round * n + k];
+                for (k, count) in counts.iter_mut().enumerate() {
+                    *count += cycles * (after(round, k) - after(start, k))
+                        + (after(start + rest, k) - after(start, k));
                 }
-            });
-        first * second
+                break;
+            }
+            seen.insert((i, item.clone()), round);
+            (i, item) = self.follow(i, item, relief, &mut counts)?;
+            history.extend_from_slice(&counts);
+        }
+        for (total, count) in inspection_counts.iter_mut().zip(counts) {
+            *total += count;
+        }
+        Ok(())
     }
 }
 
-pub async fn part<const PART: usize, const ROUNDS: usize>(
-    path: impl AsRef<Path>,
+/// The `k` monkeys that inspected the most items, busiest first, as
+/// `(monkey, inspection count)`. Ties go to the lower-numbered monkey.
+pub fn busiest(inspection_counts: &[usize], k: usize) -> Vec<(usize, usize)> {
+    let mut ranking = inspection_counts
+        .iter()
+        .copied()
+        .enumerate()
+        .collect::<Vec<_>>();
+    ranking.sort_by_key(|&(monkey, count)| (Reverse(count), monkey));
+    ranking.truncate(k);
+    ranking
+}
+
+/// The product of the `k` highest inspection counts; the puzzle uses 2.
+pub fn monkey_business_level(inspection_counts: &[usize], k: usize) -> anyhow::Result<usize> {
+    busiest(inspection_counts, k)
+        .into_iter()
+        .try_fold(1usize, |product, (_, count)| product.checked_mul(count))
+        .ok_or_else(|| anyhow::anyhow!("Monkey business overflows a usize"))
+}
+
+/// One of the items listed in the notes: the `index`th of monkey `monkey`'s
+/// starting items, from 0.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ItemId {
+    pub monkey: usize,
+    pub index: usize,
+}
+
+/// A monkey throwing an item to another.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Throw<W> {
+    /// The round it happened in, from 1.
+    pub round: usize,
+    pub from: usize,
+    pub to: usize,
+    /// The item's worry level as it was thrown.
+    pub worry: W,
+}
+
+/// What happened in a single round.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoundStats {
+    /// How many items each monkey inspected this round.
+    pub inspections: Vec<usize>,
+    /// How many items each monkey held once the round was over.
+    pub queue_lengths: Vec<usize>,
+}
+
+/// What happened over a whole run of rounds, from [`report`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Report<W> {
+    /// How many monkeys there are.
+    pub monkeys: usize,
+    /// Each round in turn.
+    pub rounds: Vec<RoundStats>,
+    /// Every throw of each traced item, in order.
+    pub journeys: HashMap<ItemId, Vec<Throw<W>>>,
+}
+
+impl<W> Report<W> {
+    /// How many items each monkey inspected over every round.
+    pub fn inspection_counts(&self) -> Vec<usize> {
+        let mut totals = vec![0; self.monkeys];
+        for round in self.rounds.iter() {
+            for (total, count) in totals.iter_mut().zip(round.inspections.iter()) {
+                *total += count;
+            }
+        }
+        totals
+    }
+
+    /// The `k` monkeys that inspected the most items over every round.
+    pub fn busiest(&self, k: usize) -> Vec<(usize, usize)> {
+        busiest(&self.inspection_counts(), k)
+    }
+}
+
+pub fn parse(input: &str) -> anyhow::Result<Monkeys> {
+    Ok(Monkeys::from_str(input)?)
+}
+
+/// How many items each monkey inspects in `rounds` rounds, with `relief`
+/// applied after every inspection and worry levels kept in a `W`, playing
+/// every round in turn. Fails if a worry level would not fit in a `W`.
+pub fn inspection_counts<W: Worry>(
+    monkeys: &Monkeys,
+    rounds: usize,
+    relief: &dyn Relief<W>,
+) -> anyhow::Result<Vec<usize>> {
+    monkeys.check_modulus(relief)?;
+    let mut state = monkeys.start::<W>();
+    for _ in 0..rounds {
+        monkeys.simulate(&mut state, relief, |_, _, _, _| {})?;
+    }
+    Ok(state.inspection_counts)
+}
+
+/// The same as [`inspection_counts`], but follows each item on its own until
+/// its journey repeats, which makes any number of rounds quick as long as it
+/// does. Relief with a [`Relief::modulus`] guarantees that it will.
+pub fn inspection_counts_by_cycles<W: Worry>(
+    monkeys: &Monkeys,
+    rounds: usize,
+    relief: &dyn Relief<W>,
+) -> anyhow::Result<Vec<usize>> {
+    monkeys.check_modulus(relief)?;
+    let mut inspection_counts = vec![0; monkeys.monkeys.len()];
+    for (i, items) in monkeys.start::<W>().items.into_iter().enumerate() {
+        for (_, item) in items {
+            monkeys.count_item_inspections((i, item), rounds, relief, &mut inspection_counts)?;
+        }
+    }
+    Ok(inspection_counts)
+}
+
+/// Plays `rounds` rounds like [`inspection_counts`], recording what happened
+/// in each one, and every throw of the items in `traced`.
+pub fn report<W: Worry>(
+    monkeys: &Monkeys,
+    rounds: usize,
+    relief: &dyn Relief<W>,
+    traced: &[ItemId],
+) -> anyhow::Result<Report<W>> {
+    monkeys.check_modulus(relief)?;
+    let mut state = monkeys.start::<W>();
+
+    // Items are numbered in the order they are listed.
+    let mut first_ids = vec![];
+    let mut next = 0;
+    for monkey in monkeys.monkeys.iter() {
+        first_ids.push(next);
+        next += monkey.items.len();
+    }
+    let mut journeys = HashMap::new();
+    for &item in traced {
+        anyhow::ensure!(
+            item.monkey < monkeys.monkeys.len()
+                && item.index < monkeys.monkeys[item.monkey].items.len(),
+            "Monkey {} has no starting item {}",
+            item.monkey,
+            item.index,
+        );
+        journeys.insert(first_ids[item.monkey] + item.index, (item, vec![]));
+    }
+
+    let mut stats = vec![];
+    for round in 1..=rounds {
+        let before = state.inspection_counts.clone();
+        monkeys.simulate(&mut state, relief, |id, from, to, worry| {
+            if let Some((_, throws)) = journeys.get_mut(&id) {
+                throws.push(Throw {
+                    round,
+                    from,
+                    to,
+                    worry: worry.clone(),
+                });
+            }
+        })?;
+        stats.push(RoundStats {
+            inspections: state
+                .inspection_counts
+                .iter()
+                .zip(before)
+                .map(|(after, before)| after - before)
+                .collect(),
+            queue_lengths: state.items.iter().map(VecDeque::len).collect(),
+        });
+    }
+
+    Ok(Report {
+        monkeys: monkeys.monkeys.len(),
+        rounds: stats,
+        journeys: journeys.into_values().collect(),
+    })
+}
+
+/// The product of the two highest inspection counts after `rounds` rounds.
+/// Relief with a modulus skips ahead with [`inspection_counts_by_cycles`];
+/// otherwise every round is played.
+pub fn monkey_business<W: Worry>(
+    monkeys: &Monkeys,
+    rounds: usize,
+    relief: &dyn Relief<W>,
 ) -> anyhow::Result<usize> {
-    let contents = tokio::fs::read_to_string(path).await?;
-    let mut monkeys = Monkeys::from_str(&contents).context("Could not parse Monkeys from file.")?;
-    for _ in 0..ROUNDS {
-        monkeys.simulate::<PART>();
+    let counts = if relief.modulus().is_some() {
+        inspection_counts_by_cycles(monkeys, rounds, relief)?
+    } else {
+        inspection_counts(monkeys, rounds, relief)?
+    };
+    monkey_business_level(&counts, 2)
+}
+
+pub fn part1(monkeys: &Monkeys) -> anyhow::Result<usize> {
+    monkey_business::<usize>(monkeys, 20, &DivideBy(3))
+}
+
+/// Without relief, work modulo the LCM of all the monkey divisors. This can't
+/// change the answer, since each test only depends on the worry level modulo
+/// its divisor, which divides the LCM. Fails for operations that can't be
+/// worked out that way.
+pub fn part2(monkeys: &Monkeys) -> anyhow::Result<usize> {
+    monkey_business::<usize>(monkeys, 10_000, &Modulo(monkeys.lcm()?))
+}
+
+pub struct Day11;
+
+impl Solution for Day11 {
+    fn day(&self) -> usize {
+        11
+    }
+
+    fn title(&self) -> &'static str {
+        "Monkey in the Middle"
+    }
+
+    fn parts(&self) -> &'static [&'static str] {
+        &[
+            "Monkey business after 20 rounds",
+            "Monkey business after 10000 rounds without relief",
+        ]
+    }
+
+    fn parse(&self, input: &str) -> anyhow::Result<Parsed> {
+        Ok(Box::new(parse(input)?))
+    }
+
+    fn solve_parsed<'a>(
+        &'a self,
+        part: usize,
+        parsed: &'a Parsed,
+    ) -> BoxFuture<'a, anyhow::Result<Answer>> {
+        Box::pin(async move {
+            let monkeys = solution::downcast::<Monkeys>(parsed)?;
+            match part {
+                1 => part1(monkeys).map(Answer::from),
+                2 => part2(monkeys).map(Answer::from),
+                _ => Err(solution::no_such_part(self, part)),
+            }
+        })
     }
-    monkeys.print_monkey_inspection_counts();
-    Ok(monkeys.monkey_business_level())
 }
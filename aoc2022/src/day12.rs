@@ -1,39 +1,20 @@
-use std::cmp::Reverse;
-use std::collections::{BinaryHeap, HashMap};
-use std::path::Path;
 use std::str::FromStr;
 
-use anyhow::Context as _;
+use futures::future::BoxFuture;
 
-type NodeId = (usize, usize);
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum NodeClass {
-    Start,
-    End,
-    None,
-}
-
-#[derive(Debug, Clone, PartialEq, Eq)]
-struct Node {
-    id: NodeId,
-    elevation: char,
-    class: NodeClass,
-}
+use crate::answer::Answer;
+use crate::grid::Grid;
+use crate::parse::ParseError;
+use crate::search::{self, Search};
+use crate::solution::{self, Parsed, Solution};
 
-impl Node {
-    fn new(i: usize, j: usize, elevation: char, class: NodeClass) -> Self {
-        Self {
-            id: (i, j),
-            elevation,
-            class,
-        }
-    }
-}
+/// A `(row, column)` grid position, which converts to and from a signed
+/// [`Point`](crate::Point).
+type NodeId = (usize, usize);
 
 struct Graph<const PART: usize> {
-    nodes: HashMap<NodeId, Node>,
-    edges: HashMap<NodeId, Vec<NodeId>>,
+    elevations: Grid<char>,
+    edges: Grid<Vec<NodeId>>,
     start: NodeId,
     end: NodeId,
 }
@@ -41,11 +22,11 @@ struct Graph<const PART: usize> {
 impl<const PART: usize> Graph<PART> {
     /// For PART == 1, this ensures next is at most 1 elevation taller than curr.
     /// For Part == 2, this ensures next is at most 1 elevation shorter than curr.
-    fn elevation_cmp(curr: &Node, next: &Node) -> bool {
+    fn elevation_cmp(curr: char, next: char) -> bool {
         if PART == 1 {
-            (next.elevation as usize) <= (curr.elevation as usize + 1)
+            (next as usize) <= (curr as usize + 1)
         } else if PART == 2 {
-            (curr.elevation as usize) <= (next.elevation as usize + 1)
+            (curr as usize) <= (next as usize + 1)
         } else {
             // TODO
             false
@@ -53,240 +34,132 @@ impl<const PART: usize> Graph<PART> {
     }
 }
 
-impl<const PART: usize> FromStr for Graph<PART> {
-    type Err = anyhow::Error;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut nodes = HashMap::new();
-        let mut start = (0, 0);
-        let mut end = (0, 0);
-
-        // Build all nodes from the str.
-        //
-        // Also record start and end Nodes.
-        for (row, line) in s.lines().enumerate() {
-            for (col, elev) in line.chars().enumerate() {
-                if elev == 'S' {
-                    nodes.insert((row, col), Node::new(row, col, 'a', NodeClass::Start));
-                    start = (row, col);
-                } else if elev == 'E' {
-                    nodes.insert((row, col), Node::new(row, col, 'z', NodeClass::End));
-                    end = (row, col);
-                } else {
-                    nodes.insert((row, col), Node::new(row, col, elev, NodeClass::None));
-                }
-            }
-        }
-
-        // Iterate over all nodes and use stored elevations to produce the edges.
-        let mut edges = HashMap::new();
-        for ((row, col), node) in nodes.iter() {
-            let entry: &mut Vec<_> = edges.entry((*row, *col)).or_default();
-
-            // Left.
-            if *col > 0 {
-                if let Some(next) = nodes.get(&(*row, *col - 1)) {
-                    if Self::elevation_cmp(node, next) {
-                        entry.push((*row, *col - 1));
-                    }
-                }
-            }
-
-            // Top.
-            if *row > 0 {
-                if let Some(next) = nodes.get(&(*row - 1, *col)) {
-                    if Self::elevation_cmp(node, next) {
-                        entry.push((*row - 1, *col));
-                    }
-                }
-            }
+/// Every square of the heightmap, and which squares are the start and end.
+#[derive(Debug, Clone)]
+pub struct Heightmap {
+    elevations: Grid<char>,
+    start: NodeId,
+    end: NodeId,
+}
 
-            // Right.
-            if let Some(next) = nodes.get(&(*row, *col + 1)) {
-                if Self::elevation_cmp(node, next) {
-                    entry.push((*row, *col + 1));
-                }
-            }
+impl FromStr for Heightmap {
+    type Err = ParseError;
 
-            // Bottom.
-            if let Some(next) = nodes.get(&(*row + 1, *col)) {
-                if Self::elevation_cmp(node, next) {
-                    entry.push((*row + 1, *col));
-                }
-            }
-        }
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let squares = Grid::parse(s, "an elevation `a`-`z`, `S` or `E`", |c| {
+            (c.is_ascii_lowercase() || c == 'S' || c == 'E').then_some(c)
+        })?;
+        let find = |square| {
+            squares
+                .enumerate()
+                .find_map(|(pos, c)| (*c == square).then_some(pos))
+        };
+        let rest = &s[s.len()..];
+        let start = find('S').ok_or_else(|| ParseError::at(s, rest, "a start square `S`"))?;
+        let end = find('E').ok_or_else(|| ParseError::at(s, rest, "an end square `E`"))?;
+
+        let elevations = squares.map(|_, c| match c {
+            'S' => 'a',
+            'E' => 'z',
+            elev => *elev,
+        });
 
         Ok(Self {
-            nodes,
-            edges,
+            elevations,
             start,
             end,
         })
     }
 }
 
-struct SearchNode<'a> {
-    node: &'a Node,
-    distance: usize,
-}
+impl<const PART: usize> Graph<PART> {
+    fn new(heightmap: &Heightmap) -> Self {
+        let elevations = heightmap.elevations.clone();
 
-impl<'a> std::fmt::Debug for SearchNode<'a> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("SearchNode")
-            .field("node", &self.node)
-            .field("distance", &self.distance)
-            .finish()
-    }
-}
+        // Use stored elevations to produce the edges to each neighbouring node.
+        let edges = elevations.map(|id, elevation| {
+            elevations
+                .neighbours4(id)
+                .filter(|next| Self::elevation_cmp(*elevation, elevations[*next]))
+                .collect()
+        });
 
-impl<'a> From<&'a Node> for SearchNode<'a> {
-    fn from(value: &'a Node) -> Self {
-        SearchNode {
-            node: value,
-            distance: 0,
+        Self {
+            elevations,
+            edges,
+            start: heightmap.start,
+            end: heightmap.end,
         }
     }
 }
 
-impl<'a> PartialEq for SearchNode<'a> {
-    fn eq(&self, other: &Self) -> bool {
-        self.node == other.node && self.distance == other.distance
+impl<const PART: usize> Graph<PART> {
+    /// Breadth-first search along the edges from `start` to the nearest goal.
+    fn search(&self, start: NodeId, is_goal: impl FnMut(&NodeId) -> bool) -> Search<NodeId, usize> {
+        search::bfs([start], |id| self.edges[*id].iter().copied(), is_goal)
     }
-}
 
-impl<'a> Eq for SearchNode<'a> {}
-
-impl<'a> PartialOrd for SearchNode<'a> {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        self.distance.partial_cmp(&other.distance)
+    /// The fewest steps from the start to the end.
+    fn shortest_path(&self) -> anyhow::Result<usize> {
+        self.search(self.start, |id| *id == self.end)
+            .goal_distance()
+            .ok_or_else(|| anyhow::anyhow!("The end cannot be reached from the start!"))
     }
-}
 
-impl<'a> Ord for SearchNode<'a> {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        self.distance.cmp(&other.distance)
+    /// The fewest steps from the end back to any square with elevation 'a'.
+    fn min_path_to_end(&self) -> anyhow::Result<usize> {
+        self.search(self.end, |id| self.elevations[*id] == 'a')
+            .goal_distance()
+            .ok_or_else(|| anyhow::anyhow!("No square with elevation a can reach the end!"))
     }
 }
 
-impl<const PART: usize> Graph<PART> {
-    /// Dijkstra's Algorithm.
-    fn shortest_path(&self) -> anyhow::Result<usize> {
-        let start = self
-            .nodes
-            .get(&self.start)
-            .map(SearchNode::from)
-            .map(Reverse)
-            .ok_or_else(|| anyhow::anyhow!("No start node available."))?;
-        let mut min_heap: BinaryHeap<Reverse<SearchNode<'_>>> = vec![start].into_iter().collect();
-
-        let mut costs: HashMap<NodeId, usize> =
-            self.nodes.iter().map(|(id, _)| (*id, usize::MAX)).collect();
-
-        while let Some(curr) = min_heap.pop() {
-            let curr_distance = curr.0.distance;
-
-            if curr.0.node.class == NodeClass::End {
-                return Ok(curr_distance);
-            }
+pub fn parse(input: &str) -> anyhow::Result<Heightmap> {
+    Ok(Heightmap::from_str(input)?)
+}
 
-            let curr_id = &curr.0.node.id;
+pub fn part1(heightmap: &Heightmap) -> anyhow::Result<usize> {
+    Graph::<1>::new(heightmap).shortest_path()
+}
 
-            if curr_distance
-                > *costs
-                    .get(curr_id)
-                    .ok_or_else(|| anyhow::anyhow!("Could not find cost with id {:#?}", curr_id))?
-            {
-                continue;
-            }
+pub fn part2(heightmap: &Heightmap) -> anyhow::Result<usize> {
+    Graph::<2>::new(heightmap).min_path_to_end()
+}
 
-            if let Some(edges) = self.edges.get(curr_id) {
-                for edge in edges {
-                    let mut node = SearchNode::from(self.nodes.get(edge).ok_or_else(|| {
-                        anyhow::anyhow!("Could not find node with id {:#?}.", edge)
-                    })?);
-                    node.distance = curr_distance + 1;
+pub struct Day12;
 
-                    if node.distance
-                        < *costs.get(edge).ok_or_else(|| {
-                            anyhow::anyhow!("Could not find cost with id {:#?}.", edge)
-                        })?
-                    {
-                        costs.insert(*edge, node.distance);
-                        min_heap.push(Reverse(node));
-                    }
-                }
-            }
-        }
-        Err(anyhow::anyhow!("The graph has no end node specified!"))
+impl Solution for Day12 {
+    fn day(&self) -> usize {
+        12
     }
 
-    /// Dijkstra's Algorithm to find the shortest path tree from end node to and node with elevation 'a'.
-    fn min_path_to_end(&self) -> anyhow::Result<usize> {
-        let end = self
-            .nodes
-            .get(&self.end)
-            .map(SearchNode::from)
-            .map(Reverse)
-            .ok_or_else(|| anyhow::anyhow!("No start node available."))?;
-        let mut min_heap: BinaryHeap<Reverse<SearchNode<'_>>> = vec![end].into_iter().collect();
-
-        let mut costs: HashMap<NodeId, usize> =
-            self.nodes.iter().map(|(id, _)| (*id, usize::MAX)).collect();
-
-        while let Some(curr) = min_heap.pop() {
-            let curr_distance = curr.0.distance;
-            let curr_id = &curr.0.node.id;
+    fn title(&self) -> &'static str {
+        "Hill Climbing Algorithm"
+    }
 
-            if curr.0.node.elevation == 'a'
-                || curr_distance
-                    > *costs.get(curr_id).ok_or_else(|| {
-                        anyhow::anyhow!("Could not find cost with id {:#?}", curr_id)
-                    })?
-            {
-                continue;
-            }
+    fn parts(&self) -> &'static [&'static str] {
+        &[
+            "Fewest steps from the start",
+            "Fewest steps from any lowest square",
+        ]
+    }
 
-            if let Some(edges) = self.edges.get(curr_id) {
-                for edge in edges {
-                    let mut node = SearchNode::from(self.nodes.get(edge).ok_or_else(|| {
-                        anyhow::anyhow!("Could not find node with id {:#?}.", edge)
-                    })?);
-                    node.distance = curr_distance + 1;
+    fn parse(&self, input: &str) -> anyhow::Result<Parsed> {
+        Ok(Box::new(parse(input)?))
+    }
 
-                    if node.distance
-                        < *costs.get(edge).ok_or_else(|| {
-                            anyhow::anyhow!("Could not find cost with id {:#?}.", edge)
-                        })?
-                    {
-                        costs.insert(*edge, node.distance);
-                        min_heap.push(Reverse(node));
-                    }
-                }
+    fn solve_parsed<'a>(
+        &'a self,
+        part: usize,
+        parsed: &'a Parsed,
+    ) -> BoxFuture<'a, anyhow::Result<Answer>> {
+        Box::pin(async move {
+            let heightmap = solution::downcast::<Heightmap>(parsed)?;
+            match part {
+                1 => part1(heightmap).map(Answer::from),
+                2 => part2(heightmap).map(Answer::from),
+                _ => Err(solution::no_such_part(self, part)),
             }
-        }
-
-        self.nodes
-            .values()
-            .filter_map(|Node { id, elevation, .. }| {
-                if *elevation == 'a' {
-                    costs.get(id).cloned()
-                } else {
-                    None
-                }
-            })
-            .min()
-            .ok_or_else(|| anyhow::anyhow!("There were no squares with elevation a in the graph!"))
+        })
     }
 }
-
-pub async fn part1(path: impl AsRef<Path>) -> anyhow::Result<usize> {
-    let contents = tokio::fs::read_to_string(path).await?;
-    let graph = Graph::<1>::from_str(&contents).context("Could not construct graph from input.")?;
-    graph.shortest_path()
-}
-
-pub async fn part2(path: impl AsRef<Path>) -> anyhow::Result<usize> {
-    let contents = tokio::fs::read_to_string(path).await?;
-    let graph = Graph::<2>::from_str(&contents).context("Could not construct graph from input.")?;
-    graph.min_path_to_end()
-}
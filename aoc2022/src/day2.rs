@@ -1,6 +1,10 @@
-use rayon::iter::ParallelIterator as _;
+use futures::future::BoxFuture;
+use rayon::iter::{IntoParallelRefIterator as _, ParallelIterator as _};
 use rayon::str::ParallelString as _;
-use std::path::Path;
+
+use crate::answer::Answer;
+use crate::parse::ParseError;
+use crate::solution::{self, Parsed, Solution};
 
 #[repr(u8)]
 #[derive(Debug, Clone, Copy)]
@@ -11,15 +15,11 @@ enum Outcome {
 }
 
 impl Outcome {
-    fn from_symbol(input: &str) -> anyhow::Result<Outcome> {
-        match input {
-            "X" => Ok(Outcome::Lost),
-            "Y" => Ok(Outcome::Draw),
-            "Z" => Ok(Outcome::Won),
-            _ => Err(anyhow::anyhow!(format!(
-                "{} is not one of the supported values (\"X\", \"Y\", \"Z\").",
-                input,
-            ))),
+    fn from_symbol(column: Column) -> Outcome {
+        match column {
+            Column::X => Outcome::Lost,
+            Column::Y => Outcome::Draw,
+            Column::Z => Outcome::Won,
         }
     }
 }
@@ -33,27 +33,21 @@ enum Play {
 }
 
 impl Play {
-    fn from_opponent(input: &str) -> anyhow::Result<Self> {
+    /// Parses `input`, a subslice of `source`.
+    fn from_opponent(source: &str, input: &str) -> Result<Self, ParseError> {
         match input {
             "A" => Ok(Play::Rock),
             "B" => Ok(Play::Paper),
             "C" => Ok(Play::Scissors),
-            _ => Err(anyhow::anyhow!(format!(
-                "{} is not one of the supported values (\"A\", \"B\", \"C\").",
-                input,
-            ))),
+            _ => Err(ParseError::at(source, input, "`A`, `B` or `C`")),
         }
     }
 
-    fn from_ipseity(input: &str) -> anyhow::Result<Self> {
-        match input {
-            "X" => Ok(Play::Rock),
-            "Y" => Ok(Play::Paper),
-            "Z" => Ok(Play::Scissors),
-            _ => Err(anyhow::anyhow!(format!(
-                "{} is not one of the supported values (\"X\", \"Y\", \"Z\")",
-                input,
-            ))),
+    fn from_ipseity(column: Column) -> Self {
+        match column {
+            Column::X => Play::Rock,
+            Column::Y => Play::Paper,
+            Column::Z => Play::Scissors,
         }
     }
 
@@ -84,27 +78,59 @@ impl Play {
     }
 }
 
-fn calculate_part1(opponent: Play, ipseity: Play) -> usize {
-    ipseity as usize + Play::outcome(opponent, ipseity) as usize
+/// The second column of the strategy guide, which each part reads differently.
+#[derive(Debug, Clone, Copy)]
+enum Column {
+    X,
+    Y,
+    Z,
 }
 
-pub fn part1(path: impl AsRef<Path>) -> anyhow::Result<usize> {
-    let contents = std::fs::read_to_string(path)?;
-    contents
+impl Column {
+    /// Parses `input`, a subslice of `source`.
+    fn from_symbol(source: &str, input: &str) -> Result<Self, ParseError> {
+        match input {
+            "X" => Ok(Column::X),
+            "Y" => Ok(Column::Y),
+            "Z" => Ok(Column::Z),
+            _ => Err(ParseError::at(source, input, "`X`, `Y` or `Z`")),
+        }
+    }
+}
+
+/// Each round of the strategy guide as (opponent's play, second column).
+#[derive(Debug, Clone)]
+pub struct Guide(Vec<(Play, Column)>);
+
+pub fn parse(input: &str) -> anyhow::Result<Guide> {
+    input
         .par_lines()
-        .map(|line| -> anyhow::Result<usize> {
-            match line.split(' ').collect::<Vec<_>>().as_slice() {
-                [opponent, ipseity] => {
-                    let opponent = Play::from_opponent(*opponent)?;
-                    let ipseity = Play::from_ipseity(*ipseity)?;
-                    Ok(calculate_part1(opponent, ipseity))
-                }
-                _ => Err(anyhow::anyhow!(format!(
-                    "{} had more than two items!",
-                    line
-                ))),
-            }
-        })
+        .map(
+            |line| match line.split(' ').collect::<Vec<_>>().as_slice() {
+                [opponent, column] => Ok((
+                    Play::from_opponent(input, opponent)?,
+                    Column::from_symbol(input, column)?,
+                )),
+                _ => Err(ParseError::at(
+                    input,
+                    line,
+                    "a play and a column, such as `A Y`",
+                )),
+            },
+        )
+        .collect::<Result<_, _>>()
+        .map(Guide)
+        .map_err(Into::into)
+}
+
+fn sum_scores(
+    guide: &Guide,
+    score: impl Fn(Play, Column) -> usize + Sync,
+) -> anyhow::Result<usize> {
+    guide
+        .0
+        .par_iter()
+        .map(|(opponent, column)| Ok(score(*opponent, *column)))
         .try_reduce(
             || 0usize,
             |a, b| {
@@ -115,6 +141,16 @@ pub fn part1(path: impl AsRef<Path>) -> anyhow::Result<usize> {
         )
 }
 
+fn calculate_part1(opponent: Play, ipseity: Play) -> usize {
+    ipseity as usize + Play::outcome(opponent, ipseity) as usize
+}
+
+pub fn part1(guide: &Guide) -> anyhow::Result<usize> {
+    sum_scores(guide, |opponent, column| {
+        calculate_part1(opponent, Play::from_ipseity(column))
+    })
+}
+
 fn calculate_part2(opponent: Play, outcome: Outcome) -> usize {
     outcome as usize
         + match outcome {
@@ -124,29 +160,46 @@ fn calculate_part2(opponent: Play, outcome: Outcome) -> usize {
         }
 }
 
-pub fn part2(path: impl AsRef<Path>) -> anyhow::Result<usize> {
-    let contents = std::fs::read_to_string(path)?;
-    contents
-        .par_lines()
-        .map(|line| -> anyhow::Result<usize> {
-            match line.split(' ').collect::<Vec<_>>().as_slice() {
-                [opponent, outcome] => {
-                    let opponent = Play::from_opponent(*opponent)?;
-                    let outcome = Outcome::from_symbol(*outcome)?;
-                    Ok(calculate_part2(opponent, outcome))
-                }
-                _ => Err(anyhow::anyhow!(format!(
-                    "{} had more than two items!",
-                    line
-                ))),
+pub fn part2(guide: &Guide) -> anyhow::Result<usize> {
+    sum_scores(guide, |opponent, column| {
+        calculate_part2(opponent, Outcome::from_symbol(column))
+    })
+}
+
+pub struct Day2;
+
+impl Solution for Day2 {
+    fn day(&self) -> usize {
+        2
+    }
+
+    fn title(&self) -> &'static str {
+        "Rock Paper Scissors"
+    }
+
+    fn parts(&self) -> &'static [&'static str] {
+        &[
+            "Score when the second column is a play",
+            "Score when the second column is an outcome",
+        ]
+    }
+
+    fn parse(&self, input: &str) -> anyhow::Result<Parsed> {
+        Ok(Box::new(parse(input)?))
+    }
+
+    fn solve_parsed<'a>(
+        &'a self,
+        part: usize,
+        parsed: &'a Parsed,
+    ) -> BoxFuture<'a, anyhow::Result<Answer>> {
+        Box::pin(async move {
+            let guide = solution::downcast::<Guide>(parsed)?;
+            match part {
+                1 => part1(guide).map(Answer::from),
+                2 => part2(guide).map(Answer::from),
+                _ => Err(solution::no_such_part(self, part)),
             }
         })
-        .try_reduce(
-            || 0usize,
-            |a, b| {
-                a.checked_add(b).ok_or_else(|| {
-                    anyhow::anyhow!("Could not add {} and {} without overflow!", a, b)
-                })
-            },
-        )
+    }
 }
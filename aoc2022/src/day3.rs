@@ -1,13 +1,40 @@
+use futures::future::BoxFuture;
 use rayon::iter::ParallelBridge as _;
-use rayon::iter::ParallelIterator as _;
+use rayon::iter::{IntoParallelRefIterator as _, ParallelIterator as _};
 use rayon::str::ParallelString as _;
 use std::collections::HashSet;
-use std::path::Path;
 
-pub fn part1(path: impl AsRef<Path>) -> anyhow::Result<usize> {
-    let contents = std::fs::read_to_string(path)?;
-    contents
+use crate::answer::Answer;
+use crate::parse::ParseError;
+use crate::solution::{self, Parsed, Solution};
+
+/// The items in each rucksack, one rucksack per line.
+#[derive(Debug, Clone)]
+pub struct Rucksacks(Vec<String>);
+
+pub fn parse(input: &str) -> anyhow::Result<Rucksacks> {
+    input
         .par_lines()
+        .map(|line| {
+            let bad_item = line.char_indices().find(|(_, c)| !c.is_ascii_alphabetic());
+            match bad_item {
+                Some((i, c)) => Err(ParseError::at(
+                    input,
+                    &line[i..i + c.len_utf8()],
+                    "an item letter `a`-`z` or `A`-`Z`",
+                )),
+                None => Ok(line.to_string()),
+            }
+        })
+        .collect::<Result<_, _>>()
+        .map(Rucksacks)
+        .map_err(Into::into)
+}
+
+pub fn part1(rucksacks: &Rucksacks) -> anyhow::Result<usize> {
+    rucksacks
+        .0
+        .par_iter()
         .map(|line| {
             let (left, right) = line.split_at(line.len() / 2);
 
@@ -35,10 +62,10 @@ pub fn part1(path: impl AsRef<Path>) -> anyhow::Result<usize> {
         )
 }
 
-pub fn part2(path: impl AsRef<Path>) -> anyhow::Result<usize> {
-    let contents = std::fs::read_to_string(path)?;
-    contents
-        .lines()
+pub fn part2(rucksacks: &Rucksacks) -> anyhow::Result<usize> {
+    rucksacks
+        .0
+        .iter()
         .array_chunks::<3>()
         .par_bridge()
         .map(|lines| {
@@ -70,3 +97,41 @@ pub fn part2(path: impl AsRef<Path>) -> anyhow::Result<usize> {
             },
         )
 }
+
+pub struct Day3;
+
+impl Solution for Day3 {
+    fn day(&self) -> usize {
+        3
+    }
+
+    fn title(&self) -> &'static str {
+        "Rucksack Reorganization"
+    }
+
+    fn parts(&self) -> &'static [&'static str] {
+        &[
+            "Priorities of misplaced items",
+            "Priorities of group badges",
+        ]
+    }
+
+    fn parse(&self, input: &str) -> anyhow::Result<Parsed> {
+        Ok(Box::new(parse(input)?))
+    }
+
+    fn solve_parsed<'a>(
+        &'a self,
+        part: usize,
+        parsed: &'a Parsed,
+    ) -> BoxFuture<'a, anyhow::Result<Answer>> {
+        Box::pin(async move {
+            let rucksacks = solution::downcast::<Rucksacks>(parsed)?;
+            match part {
+                1 => part1(rucksacks).map(Answer::from),
+                2 => part2(rucksacks).map(Answer::from),
+                _ => Err(solution::no_such_part(self, part)),
+            }
+        })
+    }
+}
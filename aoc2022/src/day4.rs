@@ -1,34 +1,49 @@
-use anyhow::Context as _;
-use rayon::iter::ParallelIterator as _;
+use futures::future::BoxFuture;
+use rayon::iter::{IntoParallelRefIterator as _, ParallelIterator as _};
 use rayon::str::ParallelString as _;
 use std::collections::HashSet;
-use std::path::Path;
 
-fn str_to_range(s: impl AsRef<str>) -> anyhow::Result<std::ops::RangeInclusive<usize>> {
-    let items = s.as_ref().split("-").collect::<Vec<_>>();
-    anyhow::ensure!(items.len() == 2, "The split term must have two pieces");
-    let left: usize = items[0]
-        .parse()
-        .with_context(|| format!("{} could not be parsed into a usize.", items[0]))?;
-    let right: usize = items[1]
-        .parse()
-        .with_context(|| format!("{} could not be parsed into a usize.", items[1]))?;
-    Ok(left..=right)
+use crate::answer::Answer;
+use crate::parse::{self, ParseError};
+use crate::solution::{self, Parsed, Solution};
+
+/// Parses `s`, a subslice of `source`, such as `2-4`.
+fn str_to_range(source: &str, s: &str) -> Result<Assignment, ParseError> {
+    match s.split('-').collect::<Vec<_>>().as_slice() {
+        [left, right] => {
+            Ok(parse::value(source, left, "usize")?..=parse::value(source, right, "usize")?)
+        }
+        _ => Err(ParseError::at(source, s, "a range such as `2-4`")),
+    }
 }
 
-pub fn part1(path: impl AsRef<Path>) -> anyhow::Result<usize> {
-    let contents = std::fs::read_to_string(path)?;
-    contents
+type Assignment = std::ops::RangeInclusive<usize>;
+
+/// The pair of section assignments on each line.
+#[derive(Debug, Clone)]
+pub struct Pairs(Vec<(Assignment, Assignment)>);
+
+pub fn parse(input: &str) -> anyhow::Result<Pairs> {
+    input
         .par_lines()
-        .map(|line| {
-            let items = line.split(",").collect::<Vec<_>>();
-            anyhow::ensure!(
-                items.len() == 2,
-                "The split line should only have two pieces."
-            );
+        .map(
+            |line| match line.split(',').collect::<Vec<_>>().as_slice() {
+                [first, second] => Ok((str_to_range(input, first)?, str_to_range(input, second)?)),
+                _ => Err(ParseError::at(input, line, "two ranges such as `2-4,6-8`")),
+            },
+        )
+        .collect::<Result<_, _>>()
+        .map(Pairs)
+        .map_err(Into::into)
+}
 
-            let first = str_to_range(items[0])?.collect::<HashSet<_>>();
-            let second = str_to_range(items[1])?.collect::<HashSet<_>>();
+pub fn part1(pairs: &Pairs) -> anyhow::Result<usize> {
+    pairs
+        .0
+        .par_iter()
+        .map(|(first, second)| {
+            let first = first.clone().collect::<HashSet<_>>();
+            let second = second.clone().collect::<HashSet<_>>();
 
             let (smaller, larger) = if first.len() > second.len() {
                 (&second, &first)
@@ -47,19 +62,13 @@ pub fn part1(path: impl AsRef<Path>) -> anyhow::Result<usize> {
         )
 }
 
-pub fn part2(path: impl AsRef<Path>) -> anyhow::Result<usize> {
-    let contents = std::fs::read_to_string(path)?;
-    contents
-        .par_lines()
-        .map(|line| {
-            let items = line.split(",").collect::<Vec<_>>();
-            anyhow::ensure!(
-                items.len() == 2,
-                "The split line should only have two pieces."
-            );
-
-            let first = str_to_range(items[0])?.collect::<HashSet<_>>();
-            let second = str_to_range(items[1])?.collect::<HashSet<_>>();
+pub fn part2(pairs: &Pairs) -> anyhow::Result<usize> {
+    pairs
+        .0
+        .par_iter()
+        .map(|(first, second)| {
+            let first = first.clone().collect::<HashSet<_>>();
+            let second = second.clone().collect::<HashSet<_>>();
 
             Ok(if first.is_disjoint(&second) { 0 } else { 1 })
         })
@@ -72,3 +81,38 @@ pub fn part2(path: impl AsRef<Path>) -> anyhow::Result<usize> {
             },
         )
 }
+
+pub struct Day4;
+
+impl Solution for Day4 {
+    fn day(&self) -> usize {
+        4
+    }
+
+    fn title(&self) -> &'static str {
+        "Camp Cleanup"
+    }
+
+    fn parts(&self) -> &'static [&'static str] {
+        &["Fully contained assignments", "Overlapping assignments"]
+    }
+
+    fn parse(&self, input: &str) -> anyhow::Result<Parsed> {
+        Ok(Box::new(parse(input)?))
+    }
+
+    fn solve_parsed<'a>(
+        &'a self,
+        part: usize,
+        parsed: &'a Parsed,
+    ) -> BoxFuture<'a, anyhow::Result<Answer>> {
+        Box::pin(async move {
+            let pairs = solution::downcast::<Pairs>(parsed)?;
+            match part {
+                1 => part1(pairs).map(Answer::from),
+                2 => part2(pairs).map(Answer::from),
+                _ => Err(solution::no_such_part(self, part)),
+            }
+        })
+    }
+}
@@ -1,76 +1,63 @@
-use anyhow::Context;
-use itertools::Itertools as _;
-use once_cell::sync::Lazy;
-use regex::Regex;
-use std::{collections::VecDeque, path::Path, str::FromStr};
+use futures::future::BoxFuture;
+use std::{collections::VecDeque, str::FromStr};
 
-#[derive(Debug, Clone, Copy, Default)]
+use crate::answer::Answer;
+use crate::parse::{self, ParseError};
+use crate::solution::{self, Parsed, Solution};
+use crate::AocParse;
+
+#[derive(Debug, Clone, Copy, Default, AocParse)]
+#[aoc(pattern = "move {quantity} from {from} to {to}")]
 struct Move {
     quantity: usize,
     from: usize,
     to: usize,
 }
 
-static REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"move (\d+) from (\d+) to (\d+)").unwrap());
-
-macro regex_capture_group_to_usize($captures:expr, $group:expr) {
-    $captures
-        .get($group)
-        .map(|m| m.as_str())
-        .ok_or_else(|| anyhow::anyhow!("Could not find match group 1."))
-        .and_then(|s| {
-            usize::from_str(s).map_err(|_| anyhow::anyhow!("Could not parse {} into usize.", s))
-        })
-}
-
-impl FromStr for Move {
-    type Err = anyhow::Error;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let captures = REGEX
-            .captures(s)
-            .ok_or_else(|| anyhow::anyhow!("No match was found in {}!", s))?;
-        let quantity = regex_capture_group_to_usize!(captures, 1)?;
-        let from = regex_capture_group_to_usize!(captures, 2)?;
-        let to = regex_capture_group_to_usize!(captures, 3)?;
-        Ok(Move { quantity, from, to })
-    }
-}
-
 #[derive(Debug, Clone)]
 struct Stacks {
     stacks: Vec<VecDeque<char>>,
 }
 
 impl FromStr for Stacks {
-    type Err = anyhow::Error;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let stacks_len = (s.find("\n").ok_or(anyhow::anyhow!("Input chart was not formatted with 3-space crates with additional one space separators."))? + 1) / 4;
+        // Crates are 3 characters wide with a 1 character separator.
+        let first_line = s.lines().next().unwrap_or(s);
+        let stacks_len = (first_line.len() + 1) / 4;
         let mut stacks = vec![VecDeque::new(); stacks_len];
+        // The last line only numbers the stacks.
         for line in s.lines().rev().skip(1) {
-            for (i, item) in line.bytes().into_iter().chunks(4).into_iter().enumerate() {
-                let vec = item.into_iter().collect::<Vec<_>>();
-                let s = std::str::from_utf8(&vec)
-                    .context("Chunk was parsed incorrectly in a way unable to recreate a str.")?;
-                if s.trim() == "" {
+            for (i, start) in (0..line.len()).step_by(4).enumerate() {
+                let item = &line[start..(start + 3).min(line.len())];
+                if item.trim() == "" {
                     continue;
                 }
-                let indicator = s.chars().nth(1).ok_or_else(|| {
-                    anyhow::anyhow!("Could not retreive the crate letter from the item.")
-                })?;
-                stacks[i].push_back(indicator);
+                if i >= stacks_len {
+                    return Err(ParseError::at(
+                        s,
+                        item,
+                        format!("at most {} stacks", stacks_len),
+                    ));
+                }
+                match item.as_bytes() {
+                    [b'[', indicator, b']'] if indicator.is_ascii_alphabetic() => {
+                        stacks[i].push_back(*indicator as char)
+                    }
+                    _ => return Err(ParseError::at(s, item, "a crate such as `[A]`")),
+                }
             }
         }
         Ok(Self { stacks })
     }
 }
 
-fn get_stacks_as_mut_ref<'a>(
-    stacks: &'a mut Stacks,
+fn get_stacks_as_mut_ref(
+    stacks: &mut Stacks,
     from: usize,
     to: usize,
-) -> (&'a mut VecDeque<char>, &'a mut VecDeque<char>) {
+) -> (&mut VecDeque<char>, &mut VecDeque<char>) {
     let min = std::cmp::min(from, to);
     let (left, right) = stacks.stacks.split_at_mut(min + 1);
     if min == from {
@@ -115,38 +102,82 @@ impl Stacks {
     }
 }
 
-pub async fn part<const PART: usize>(path: impl AsRef<Path>) -> anyhow::Result<String> {
-    let contents = tokio::fs::read_to_string(path).await?;
-
-    // This really shouldn't leak. I thought I would be able to unleak it at the
-    // bottom of the function, but it complains about the &mut str being
-    // borrowed too long (for 'static).
-    let contents = contents.leak();
-
-    let (mut stacks, moves): (_, Vec<_>) =
-        match contents.split_at(contents.find("\n\n").unwrap_or(contents.len())) {
-            (stacks, moves) => {
-                let stacks = Stacks::from_str(stacks)?;
-                let moves = futures::future::join_all(
-                    moves
-                        .trim()
-                        .lines()
-                        .map(|line| tokio::spawn(async move { Move::from_str(line) })),
-                )
-                .await
-                .into_iter()
-                .collect::<Result<_, _>>()?;
-                (stacks, moves)
-            }
-        };
+/// The starting stacks of crates and the rearrangement procedure.
+#[derive(Debug, Clone)]
+pub struct Drawing {
+    stacks: Stacks,
+    moves: Vec<Move>,
+}
 
-    for maybe_move in moves.into_iter() {
-        if let Err(err) = maybe_move {
-            anyhow::bail!(err);
-        }
+pub fn parse(input: &str) -> anyhow::Result<Drawing> {
+    let (stacks, moves) = input.split_at(input.find("\n\n").unwrap_or(input.len()));
+    let stacks = Stacks::from_str(stacks)?;
+    let moves = moves
+        .trim()
+        .lines()
+        .map(|line| {
+            let m = Move::from_str(line)
+                .map_err(|err| err.offset_lines(parse::line_index(input, line)))?;
+            // Stacks are numbered from 1, and the pattern puts them in the
+            // 4th and 6th words.
+            let words = line.split(' ').collect::<Vec<_>>();
+            for (stack, text) in [(m.from, words[3]), (m.to, words[5])] {
+                if !(1..=stacks.stacks.len()).contains(&stack) {
+                    return Err(ParseError::at(
+                        input,
+                        text,
+                        format!("a stack from 1 to {}", stacks.stacks.len()),
+                    ));
+                }
+            }
+            Ok(m)
+        })
+        .collect::<Result<_, _>>()?;
+    Ok(Drawing { stacks, moves })
+}
 
-        let m = maybe_move.unwrap();
-        stacks.simulate::<PART>(m)?;
+pub fn part<const PART: usize>(drawing: &Drawing) -> anyhow::Result<String> {
+    let mut stacks = drawing.stacks.clone();
+    for m in drawing.moves.iter() {
+        stacks.simulate::<PART>(*m)?;
     }
     stacks.tops()
 }
+
+pub struct Day5;
+
+impl Solution for Day5 {
+    fn day(&self) -> usize {
+        5
+    }
+
+    fn title(&self) -> &'static str {
+        "Supply Stacks"
+    }
+
+    fn parts(&self) -> &'static [&'static str] {
+        &[
+            "Top crates when moved one at a time",
+            "Top crates when moved all at once",
+        ]
+    }
+
+    fn parse(&self, input: &str) -> anyhow::Result<Parsed> {
+        Ok(Box::new(parse(input)?))
+    }
+
+    fn solve_parsed<'a>(
+        &'a self,
+        part: usize,
+        parsed: &'a Parsed,
+    ) -> BoxFuture<'a, anyhow::Result<Answer>> {
+        Box::pin(async move {
+            let drawing = solution::downcast::<Drawing>(parsed)?;
+            match part {
+                1 => self::part::<1>(drawing).map(Answer::from),
+                2 => self::part::<2>(drawing).map(Answer::from),
+                _ => Err(solution::no_such_part(self, part)),
+            }
+        })
+    }
+}
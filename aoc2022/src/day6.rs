@@ -1,4 +1,7 @@
-use std::path::Path;
+use futures::future::BoxFuture;
+
+use crate::answer::Answer;
+use crate::solution::{self, Parsed, Solution};
 
 struct Window<const N: usize> {
     chars: [char; N],
@@ -25,11 +28,19 @@ impl<const N: usize> Window<N> {
     }
 }
 
-pub async fn part<const LEN: usize>(path: impl AsRef<Path>) -> anyhow::Result<usize> {
-    let contents = tokio::fs::read_to_string(path).await?;
+/// The characters received by the device.
+#[derive(Debug, Clone)]
+pub struct Datastream(Vec<char>);
+
+pub fn parse(input: &str) -> anyhow::Result<Datastream> {
+    Ok(Datastream(input.chars().collect()))
+}
 
-    let initial: [char; LEN] = contents
-        .chars()
+pub fn part<const LEN: usize>(datastream: &Datastream) -> anyhow::Result<usize> {
+    let initial: [char; LEN] = datastream
+        .0
+        .iter()
+        .copied()
         .take(LEN)
         .collect::<Vec<_>>()
         .try_into()
@@ -38,8 +49,10 @@ pub async fn part<const LEN: usize>(path: impl AsRef<Path>) -> anyhow::Result<us
     if window.unique() {
         return Ok(4);
     }
-    for (i, c) in contents
-        .chars()
+    for (i, c) in datastream
+        .0
+        .iter()
+        .copied()
         .enumerate()
         .map(|(i, c)| (i + 1, c))
         .skip(LEN)
@@ -53,3 +66,38 @@ pub async fn part<const LEN: usize>(path: impl AsRef<Path>) -> anyhow::Result<us
         "There were not four consecutive different characters in the input!"
     ))
 }
+
+pub struct Day6;
+
+impl Solution for Day6 {
+    fn day(&self) -> usize {
+        6
+    }
+
+    fn title(&self) -> &'static str {
+        "Tuning Trouble"
+    }
+
+    fn parts(&self) -> &'static [&'static str] {
+        &["Start-of-packet marker", "Start-of-message marker"]
+    }
+
+    fn parse(&self, input: &str) -> anyhow::Result<Parsed> {
+        Ok(Box::new(parse(input)?))
+    }
+
+    fn solve_parsed<'a>(
+        &'a self,
+        part: usize,
+        parsed: &'a Parsed,
+    ) -> BoxFuture<'a, anyhow::Result<Answer>> {
+        Box::pin(async move {
+            let datastream = solution::downcast::<Datastream>(parsed)?;
+            match part {
+                1 => self::part::<4>(datastream).map(Answer::from),
+                2 => self::part::<14>(datastream).map(Answer::from),
+                _ => Err(solution::no_such_part(self, part)),
+            }
+        })
+    }
+}
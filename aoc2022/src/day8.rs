@@ -1,159 +1,117 @@
-use std::path::Path;
 use std::str::FromStr;
 use std::sync::Arc;
 
 use anyhow::Context;
+use futures::future::BoxFuture;
 use itertools::Itertools;
 
+use crate::answer::Answer;
+use crate::grid::Grid;
+use crate::parse::ParseError;
+use crate::solution::{self, Parsed, Solution};
+
+/// The height of every tree in the grid.
 #[derive(Debug, Clone)]
-struct Trees(Vec<Vec<usize>>);
+pub struct Trees(Grid<usize>);
 
 impl FromStr for Trees {
-    type Err = anyhow::Error;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut field = vec![];
-        for line in s.lines() {
-            let mut field_line = vec![];
-            for c in line.chars() {
-                field_line.push(
-                    c.to_digit(10)
-                        .map(|i| i as usize)
-                        .ok_or_else(|| anyhow::anyhow!("{} is not a digit!", c))?,
-                );
-            }
-            field.push(field_line);
+        let grid = Grid::parse(s, "a tree height digit", |c| {
+            c.to_digit(10).map(|i| i as usize)
+        })?;
+        if grid.width() == 0 {
+            return Err(ParseError::at(s, s, "a grid of tree heights"));
         }
-        Ok(Trees(field))
+        Ok(Trees(grid))
     }
 }
 
-impl Trees {
-    fn len(&self) -> usize {
-        self.0.len()
-    }
-
-    fn perimeter(&self) -> usize {
-        4 * self.0.len() - 4
-    }
-
-    fn visible_from_north(&self) -> Vec<Vec<bool>> {
-        let n = self.len();
-        let mut highest = self.0[0].clone();
-        let mut field = vec![vec![false; n]; n];
-        for i in 1..(n - 1) {
-            for j in 1..(n - 1) {
-                let val = self.0[i][j];
-                if val > highest[j] {
-                    field[i][j] = true;
-                    highest[j] = val;
-                }
+/// Marks the interior trees of `trees` that are visible from the north edge.
+fn visible_from_north(trees: &Grid<usize>) -> Grid<bool> {
+    let (height, width) = (trees.height(), trees.width());
+    let mut highest = trees.row(0).to_vec();
+    let mut field = Grid::new(width, height, false);
+    for i in 1..height.saturating_sub(1) {
+        for j in 1..width.saturating_sub(1) {
+            let val = trees[(i, j)];
+            if val > highest[j] {
+                field[(i, j)] = true;
+                highest[j] = val;
             }
         }
-        field
     }
+    field
+}
 
-    fn visible_from_south(&self) -> Vec<Vec<bool>> {
-        let n = self.len();
-        let mut highest = self.0[n - 1].clone();
-        let mut field = vec![vec![false; n]; n];
-        for i in (1..(n - 1)).rev() {
-            for j in 1..(n - 1) {
-                let val = self.0[i][j];
-                if val > highest[j] {
-                    field[i][j] = true;
-                    highest[j] = val;
-                }
-            }
+impl Trees {
+    fn perimeter(&self) -> usize {
+        let (height, width) = (self.0.height(), self.0.width());
+        if height < 2 || width < 2 {
+            height * width
+        } else {
+            2 * (height + width) - 4
         }
-        field
     }
 
-    fn visible_from_west(&self) -> Vec<Vec<bool>> {
-        let n = self.len();
-
-        let mut highest = vec![0usize; n];
-        for i in 0..n {
-            highest[i] = self.0[i][0];
-        }
+    /// The interior positions, which are the only ones that can be hidden.
+    fn interior(&self) -> impl Iterator<Item = (usize, usize)> {
+        let (height, width) = (self.0.height(), self.0.width());
+        (1..height.saturating_sub(1)).cartesian_product(1..width.saturating_sub(1))
+    }
 
-        let mut field = vec![vec![false; n]; n];
-        for i in 1..(n - 1) {
-            for j in 1..(n - 1) {
-                let val = self.0[i][j];
-                if val > highest[i] {
-                    field[i][j] = true;
-                    highest[i] = val;
-                }
-            }
-        }
-        field
+    fn visible_from_north(&self) -> Grid<bool> {
+        visible_from_north(&self.0)
     }
 
-    fn visible_from_east(&self) -> Vec<Vec<bool>> {
-        let n = self.len();
+    fn visible_from_south(&self) -> Grid<bool> {
+        let rotated = self.0.rotate_clockwise().rotate_clockwise();
+        visible_from_north(&rotated)
+            .rotate_clockwise()
+            .rotate_clockwise()
+    }
 
-        let mut highest = vec![0usize; n];
-        for i in 0..n {
-            highest[i] = self.0[i][n - 1];
-        }
+    fn visible_from_west(&self) -> Grid<bool> {
+        visible_from_north(&self.0.rotate_clockwise()).rotate_counterclockwise()
+    }
 
-        let mut field = vec![vec![false; n]; n];
-        for i in 1..(n - 1) {
-            for j in (1..(n - 1)).rev() {
-                let val = self.0[i][j];
-                if val > highest[i] {
-                    field[i][j] = true;
-                    highest[i] = val;
-                }
-            }
-        }
-        field
+    fn visible_from_east(&self) -> Grid<bool> {
+        visible_from_north(&self.0.rotate_counterclockwise()).rotate_clockwise()
     }
 
     fn scenic_number(&self, i: usize, j: usize) -> usize {
-        let n = self.len();
-
-        let mut left = 0;
-        let mut right = 0;
-        for c in (0..j).rev() {
-            left += 1;
-            if self.0[i][c] >= self.0[i][j] {
-                break;
-            }
-        }
-        for c in (j + 1)..n {
-            right += 1;
-            if self.0[i][c] >= self.0[i][j] {
-                break;
+        let trees = &self.0;
+        let height = trees[(i, j)];
+        // Counts the trees in view along a line of sight, up to and including
+        // the first one that blocks it.
+        let viewing_distance = |line: &mut dyn Iterator<Item = &usize>| {
+            let mut count = 0;
+            for tree in line {
+                count += 1;
+                if *tree >= height {
+                    break;
+                }
             }
-        }
+            count
+        };
 
-        let mut top = 0;
-        let mut bottom = 0;
-        for c in (0..i).rev() {
-            top += 1;
-            if self.0[c][j] >= self.0[i][j] {
-                break;
-            }
-        }
-        for c in (i + 1)..n {
-            bottom += 1;
-            if self.0[c][j] >= self.0[i][j] {
-                break;
-            }
-        }
+        let left = viewing_distance(&mut trees.row(i)[..j].iter().rev());
+        let right = viewing_distance(&mut trees.row(i)[j + 1..].iter());
+        let column = trees.column(j).collect::<Vec<_>>();
+        let top = viewing_distance(&mut column[..i].iter().rev().copied());
+        let bottom = viewing_distance(&mut column[i + 1..].iter().copied());
 
         left * right * top * bottom
     }
 }
 
-pub async fn part1(path: impl AsRef<Path>) -> anyhow::Result<usize> {
-    let contents = tokio::fs::read_to_string(path).await?;
-
-    let trees = Arc::new(Trees::from_str(&contents)?);
-    let n = trees.len();
+/// Shared so that each part can hand the grid to its spawned tasks.
+pub fn parse(input: &str) -> anyhow::Result<Arc<Trees>> {
+    Ok(Arc::new(Trees::from_str(input)?))
+}
 
+pub async fn part1(trees: &Arc<Trees>) -> anyhow::Result<usize> {
     let north = tokio::spawn({
         let trees = trees.clone();
         async move { trees.visible_from_north() }
@@ -176,24 +134,15 @@ pub async fn part1(path: impl AsRef<Path>) -> anyhow::Result<usize> {
     let west = west.await.context("West task failed!")?;
     let east = east.await.context("East task failed!")?;
 
-    let mut interior_visible_count = 0;
-    for i in 1..(n - 1) {
-        for j in 1..(n - 1) {
-            if north[i][j] || south[i][j] || west[i][j] || east[i][j] {
-                interior_visible_count += 1;
-            }
-        }
-    }
+    let interior_visible_count = trees
+        .interior()
+        .filter(|&pos| north[pos] || south[pos] || west[pos] || east[pos])
+        .count();
     Ok(trees.perimeter() + interior_visible_count)
 }
 
-pub async fn part2(path: impl AsRef<Path>) -> anyhow::Result<usize> {
-    let contents = tokio::fs::read_to_string(path).await?;
-
-    let trees = Arc::new(Trees::from_str(&contents)?);
-    let n = trees.len();
-
-    futures::future::join_all((1..(n - 1)).cartesian_product(1..(n - 1)).map(|(i, j)| {
+pub async fn part2(trees: &Arc<Trees>) -> anyhow::Result<usize> {
+    futures::future::join_all(trees.interior().map(|(i, j)| {
         let trees = trees.clone();
         tokio::spawn(async move { trees.scenic_number(i, j) })
     }))
@@ -208,3 +157,41 @@ pub async fn part2(path: impl AsRef<Path>) -> anyhow::Result<usize> {
     .map_err(|err| err.into())
     .flatten()
 }
+
+pub struct Day8;
+
+impl Solution for Day8 {
+    fn day(&self) -> usize {
+        8
+    }
+
+    fn title(&self) -> &'static str {
+        "Treetop Tree House"
+    }
+
+    fn parts(&self) -> &'static [&'static str] {
+        &[
+            "Trees visible from outside the grid",
+            "Highest scenic score",
+        ]
+    }
+
+    fn parse(&self, input: &str) -> anyhow::Result<Parsed> {
+        Ok(Box::new(parse(input)?))
+    }
+
+    fn solve_parsed<'a>(
+        &'a self,
+        part: usize,
+        parsed: &'a Parsed,
+    ) -> BoxFuture<'a, anyhow::Result<Answer>> {
+        Box::pin(async move {
+            let trees = solution::downcast::<Arc<Trees>>(parsed)?;
+            match part {
+                1 => part1(trees).await.map(Answer::from),
+                2 => part2(trees).await.map(Answer::from),
+                _ => Err(solution::no_such_part(self, part)),
+            }
+        })
+    }
+}
@@ -1,205 +1,364 @@
 use std::collections::HashSet;
-use std::path::Path;
 use std::str::FromStr;
 
-use anyhow::Context as _;
+use futures::future::BoxFuture;
 
-#[derive(Debug)]
-enum Move {
+use crate::answer::Answer;
+use crate::grid::Grid;
+use crate::parse;
+use crate::point::{Coords, Direction8, Point};
+use crate::solution::{self, Parsed, Solution};
+use crate::AocParse;
+
+#[derive(Debug, Clone, Copy, AocParse)]
+pub enum Move {
+    #[aoc(pattern = "L {0}")]
     Left(usize),
+    #[aoc(pattern = "U {0}")]
     Up(usize),
+    #[aoc(pattern = "R {0}")]
     Right(usize),
+    #[aoc(pattern = "D {0}")]
     Down(usize),
+    #[aoc(pattern = "UL {0}")]
+    UpLeft(usize),
+    #[aoc(pattern = "UR {0}")]
+    UpRight(usize),
+    #[aoc(pattern = "DL {0}")]
+    DownLeft(usize),
+    #[aoc(pattern = "DR {0}")]
+    DownRight(usize),
+    #[aoc(pattern = "F {0}")]
+    Forward(usize),
+    #[aoc(pattern = "B {0}")]
+    Back(usize),
 }
 
-impl FromStr for Move {
-    type Err = anyhow::Error;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let ty = s
-            .chars()
-            .nth(0)
-            .ok_or_else(|| anyhow::anyhow!("Line did not have any characters!"))?;
-        let count = s
-            .get(2..)
-            .ok_or_else(|| anyhow::anyhow!(""))
-            .map(str::trim)
-            .and_then(|s| {
-                usize::from_str(s).with_context(|| format!("Could not parse {} into a usize.", s))
-            })?;
-        match ty {
-            'L' => Ok(Move::Left(count)),
-            'U' => Ok(Move::Up(count)),
-            'R' => Ok(Move::Right(count)),
-            'D' => Ok(Move::Down(count)),
-            other => anyhow::bail!("Unknown Move type: {}.", other),
+impl Move {
+    /// A single step of the move along x (right), y (down) and z (forward).
+    fn unit(self) -> [i64; 3] {
+        let planar = |direction: Direction8| {
+            let offset = direction.offset();
+            [offset.x, offset.y, 0]
+        };
+        match self {
+            Move::Left(_) => planar(Direction8::Left),
+            Move::Up(_) => planar(Direction8::Up),
+            Move::Right(_) => planar(Direction8::Right),
+            Move::Down(_) => planar(Direction8::Down),
+            Move::UpLeft(_) => planar(Direction8::UpLeft),
+            Move::UpRight(_) => planar(Direction8::UpRight),
+            Move::DownLeft(_) => planar(Direction8::DownLeft),
+            Move::DownRight(_) => planar(Direction8::DownRight),
+            Move::Forward(_) => [0, 0, 1],
+            Move::Back(_) => [0, 0, -1],
+        }
+    }
+
+    /// A single step of the move in `P`'s coordinates, if it has enough axes.
+    fn offset<P: Coords>(self) -> anyhow::Result<P> {
+        let unit = self.unit();
+        if let Some(axis) = (P::DIMENSIONS..3).find(|axis| unit[*axis] != 0) {
+            anyhow::bail!(
+                "`{:?}` moves along axis {}, but the rope only has {} dimensions",
+                self,
+                axis,
+                P::DIMENSIONS
+            );
+        }
+        Ok(P::from_fn(|axis| unit.get(axis).copied().unwrap_or(0)))
+    }
+
+    fn steps(self) -> usize {
+        match self {
+            Move::Left(steps)
+            | Move::Up(steps)
+            | Move::Right(steps)
+            | Move::Down(steps)
+            | Move::UpLeft(steps)
+            | Move::UpRight(steps)
+            | Move::DownLeft(steps)
+            | Move::DownRight(steps)
+            | Move::Forward(steps)
+            | Move::Back(steps) => steps,
         }
     }
 }
 
-type Point = (usize, usize);
-
-fn move_knot_to_knot(first: Point, second: Point) -> Point {
-    let mut res = second;
-    if first.0.abs_diff(second.0) > 1 || first.1.abs_diff(second.1) > 1 {
-        if first.0 == second.0 {
-            // head.1 != tail.1
-            if first.1 + 1 > second.1 {
-                res.1 += 1;
-            } else if first.1 < second.1 + 1 {
-                res.1 -= 1;
-            }
-        } else if first.1 == second.1 {
-            // head.0 != tail.0
-            if first.0 + 1 > second.0 {
-                res.0 += 1;
-            } else if first.0 < second.0 + 1 {
-                res.0 -= 1;
-            }
-        } else {
-            // head.0 != tail.0 && head.1 != tail.1
-            if first.0 > second.0 {
-                res.0 += 1;
-            } else {
-                // head.0 < tail.0
-                res.0 -= 1;
-            }
+/// Which cells count as next to each other when deciding if a knot is too far
+/// from the one before it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Connectivity {
+    /// Diagonal neighbours are one step away: 8 of them in 2D, 26 in 3D.
+    Eight,
+    /// Diagonal neighbours are two or more steps away: 4 neighbours in 2D, 6
+    /// in 3D.
+    Four,
+}
 
-            if first.1 > second.1 {
-                res.1 += 1;
-            } else {
-                // head.1 < tail.1
-                res.1 -= 1;
-            }
+/// How each knot follows the one before it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Physics {
+    /// How many steps a knot may be from the one before it before it moves.
+    pub slack: u64,
+    /// How steps are counted for `slack`.
+    pub connectivity: Connectivity,
+    /// Whether a knot that moves may step diagonally, rather than only along
+    /// the axis it is furthest behind on.
+    pub diagonal_steps: bool,
+}
+
+/// The puzzle's physics: knots must touch, diagonals included, and catch up
+/// diagonally.
+impl Default for Physics {
+    fn default() -> Self {
+        Self {
+            slack: 1,
+            connectivity: Connectivity::Eight,
+            diagonal_steps: true,
         }
     }
-    res
 }
 
-macro move_impl_1($head:ident, $tail:ident, $tail_visited:ident, $new_head:expr, $amount:expr $(,)?) {
-    for _ in 0..$amount {
-        $head = $new_head;
-        $tail = move_knot_to_knot($head, $tail);
-        $tail_visited.insert($tail);
+impl Physics {
+    fn distance<P: Coords>(&self, a: P, b: P) -> u64 {
+        let gaps = (0..P::DIMENSIONS).map(|axis| a.axis(axis).abs_diff(b.axis(axis)));
+        match self.connectivity {
+            Connectivity::Eight => gaps.max().unwrap_or(0),
+            Connectivity::Four => gaps.sum(),
+        }
     }
+
+    /// One step from `knot` towards `target`: diagonal if allowed, otherwise
+    /// along the axis with the largest gap, preferring earlier axes.
+    fn step_towards<P: Coords>(&self, knot: P, target: P) -> P {
+        let gap = |axis| target.axis(axis) - knot.axis(axis);
+        if self.diagonal_steps {
+            P::from_fn(|axis| knot.axis(axis) + gap(axis).signum())
+        } else {
+            let furthest = (0..P::DIMENSIONS)
+                .rev()
+                .max_by_key(|axis| gap(*axis).abs())
+                .unwrap_or(0);
+            P::from_fn(|axis| {
+                knot.axis(axis)
+                    + if axis == furthest {
+                        gap(axis).signum()
+                    } else {
+                        0
+                    }
+            })
+        }
+    }
+}
+
+/// Moves `second` towards `first` until it is within the slack again, calling
+/// `visit` with every cell it steps into on the way.
+fn move_knot_to_knot<P: Coords>(
+    first: P,
+    second: P,
+    physics: &Physics,
+    mut visit: impl FnMut(P),
+) -> P {
+    let mut second = second;
+    while physics.distance(first, second) > physics.slack {
+        second = physics.step_towards(second, first);
+        visit(second);
+    }
+    second
 }
 
-pub async fn part1(path: impl AsRef<Path>) -> anyhow::Result<usize> {
-    let contents = tokio::fs::read_to_string(path).await?;
-    let moves = contents
+pub fn parse(input: &str) -> anyhow::Result<Vec<Move>> {
+    input
         .lines()
-        .map(Move::from_str)
-        .collect::<Result<Vec<_>, _>>()?;
-
-    let mut head = (1000, 1000);
-    let mut tail = (1000, 1000);
-    let mut tail_visited = vec![tail].into_iter().collect::<HashSet<_>>();
-    for m in moves {
-        match m {
-            Move::Left(amount) => {
-                move_impl_1!(head, tail, tail_visited, (head.0 - 1, head.1), amount)
-            }
-            Move::Up(amount) => {
-                move_impl_1!(head, tail, tail_visited, (head.0, head.1 + 1), amount)
-            }
-            Move::Right(amount) => {
-                move_impl_1!(head, tail, tail_visited, (head.0 + 1, head.1), amount)
-            }
-            Move::Down(amount) => {
-                move_impl_1!(head, tail, tail_visited, (head.0, head.1 - 1), amount)
+        .map(|line| {
+            Move::from_str(line).map_err(|err| err.offset_lines(parse::line_index(input, line)))
+        })
+        .collect::<Result<_, _>>()
+        .map_err(Into::into)
+}
+
+/// A rope of any number of knots, which all start at the origin, and every
+/// cell each knot has been in. Ropes are in the puzzle's 2D plane unless `P`
+/// says otherwise, e.g. `Rope<[i64; 3]>`.
+#[derive(Debug, Clone)]
+pub struct Rope<P = Point> {
+    knots: Vec<P>,
+    visited: Vec<HashSet<P>>,
+    physics: Physics,
+}
+
+impl<P: Coords> Rope<P> {
+    /// Panics if `knots` is 0, since a rope needs a head.
+    pub fn new(knots: usize, physics: Physics) -> Self {
+        assert!(knots > 0, "a rope needs at least one knot");
+        Self {
+            knots: vec![P::origin(); knots],
+            visited: vec![HashSet::from([P::origin()]); knots],
+            physics,
+        }
+    }
+
+    /// Every knot, from the head to the tail.
+    pub fn knots(&self) -> &[P] {
+        &self.knots
+    }
+
+    pub fn head(&self) -> P {
+        self.knots[0]
+    }
+
+    pub fn tail(&self) -> P {
+        *self.knots.last().unwrap()
+    }
+
+    /// Every cell knot `knot` has been in, counting the head as knot 0. Knots
+    /// never look behind them, so this is also where the tail of a
+    /// `knot + 1`-knot rope would have been.
+    pub fn visited(&self, knot: usize) -> &HashSet<P> {
+        &self.visited[knot]
+    }
+
+    /// Moves the head by `step`, then each knot after it in turn.
+    pub fn step(&mut self, step: P) {
+        self.knots[0] = self.knots[0].translate(step);
+        self.visited[0].insert(self.knots[0]);
+        for i in 1..self.knots.len() {
+            let visited = &mut self.visited[i];
+            let moved =
+                move_knot_to_knot(self.knots[i - 1], self.knots[i], &self.physics, |cell| {
+                    visited.insert(cell);
+                });
+            if moved == self.knots[i] {
+                // Nothing further along can move either.
+                break;
             }
+            self.knots[i] = moved;
+        }
+    }
+
+    /// Fails if the move is along an axis the rope doesn't have.
+    pub fn apply(&mut self, m: Move) -> anyhow::Result<()> {
+        let step = m.offset()?;
+        for _ in 0..m.steps() {
+            self.step(step);
         }
+        Ok(())
     }
-    Ok(tail_visited.len())
 }
 
-macro move_impl_2(
-    $head:ident, 
-    $tail1:ident, 
-    $tail2:ident, 
-    $tail3:ident, 
-    $tail4:ident, 
-    $tail5:ident, 
-    $tail6:ident, 
-    $tail7:ident, 
-    $tail8:ident,
-    $tail9:ident,
-    $tail_visited:ident, 
-    $new_head:expr,
-    $amount:expr $(,)?
-) {
-    for _ in 0..$amount {
-        $head = $new_head;
-        $tail1 = move_knot_to_knot($head, $tail1);
-        $tail2 = move_knot_to_knot($tail1, $tail2);
-        $tail3 = move_knot_to_knot($tail2, $tail3);
-        $tail4 = move_knot_to_knot($tail3, $tail4);
-        $tail5 = move_knot_to_knot($tail4, $tail5);
-        $tail6 = move_knot_to_knot($tail5, $tail6);
-        $tail7 = move_knot_to_knot($tail6, $tail7);
-        $tail8 = move_knot_to_knot($tail7, $tail8);
-        $tail9 = move_knot_to_knot($tail8, $tail9);
-        $tail_visited.insert($tail9);
+impl Rope<Point> {
+    /// Draws the rope like the puzzle does, over the cells the tail has
+    /// visited. See [`Rope::render_trail`].
+    pub fn render(&self) -> String {
+        self.render_trail(self.knots.len() - 1)
+    }
+
+    /// Draws every cell knot `knot` has visited as `#`, the start as `s`, and
+    /// the knots where they are now as `H`, `1` to `9`, then `*` for any
+    /// further along, with earlier knots on top. The picture is just big
+    /// enough for all of them. Apply moves one at a time to draw the rope
+    /// partway through.
+    pub fn render_trail(&self, knot: usize) -> String {
+        let cells = || {
+            self.visited[knot]
+                .iter()
+                .chain(self.knots.iter())
+                .chain([&Point::ORIGIN])
+        };
+        let min = Point::new(
+            cells().map(|p| p.x).min().unwrap(),
+            cells().map(|p| p.y).min().unwrap(),
+        );
+        let max = Point::new(
+            cells().map(|p| p.x).max().unwrap(),
+            cells().map(|p| p.y).max().unwrap(),
+        );
+
+        let mut picture = Grid::new(
+            (max.x - min.x + 1) as usize,
+            (max.y - min.y + 1) as usize,
+            '.',
+        );
+        let mut draw = |point: Point, c: char| {
+            let pos = <(usize, usize)>::try_from(point - min).unwrap();
+            picture[pos] = c;
+        };
+        for point in self.visited[knot].iter() {
+            draw(*point, '#');
+        }
+        draw(Point::ORIGIN, 's');
+        for (i, point) in self.knots.iter().enumerate().rev() {
+            let label = match i {
+                0 => 'H',
+                1..=9 => char::from_digit(i as u32, 10).unwrap(),
+                _ => '*',
+            };
+            draw(*point, label);
+        }
+        picture.to_string()
     }
 }
 
-pub async fn part2(path: impl AsRef<Path>) -> anyhow::Result<usize> {
-    let contents = tokio::fs::read_to_string(path).await?;
-    let moves = contents
-        .lines()
-        .map(Move::from_str)
-        .collect::<Result<Vec<_>, _>>()?;
-
-    let mut head = (1000, 1000);
-    let mut tail1 = head;
-    let mut tail2 = head;
-    let mut tail3 = head;
-    let mut tail4 = head;
-    let mut tail5 = head;
-    let mut tail6 = head;
-    let mut tail7 = head;
-    let mut tail8 = head;
-    let mut tail9 = head;
-    let mut tail_visited = vec![tail9].into_iter().collect::<HashSet<_>>();
-    for m in moves {
-        match m {
-            Move::Left(amount) => {
-                move_impl_2!(
-                    head, 
-                    tail1, tail2, tail3, tail4, tail5, tail6, tail7, tail8, tail9,
-                    tail_visited, 
-                    (head.0 - 1, head.1),
-                    amount,
-                )
-            }
-            Move::Up(amount) => {
-                move_impl_2!(
-                    head, 
-                    tail1, tail2, tail3, tail4, tail5, tail6, tail7, tail8, tail9,
-                    tail_visited, 
-                    (head.0, head.1 + 1),
-                    amount,
-                )
-            }
-            Move::Right(amount) => {
-                move_impl_2!(
-                    head, 
-                    tail1, tail2, tail3, tail4, tail5, tail6, tail7, tail8, tail9,
-                    tail_visited, 
-                    (head.0 + 1, head.1), 
-                    amount,
-                )
-            }
-            Move::Down(amount) => {
-                move_impl_2!(
-                    head, 
-                    tail1, tail2, tail3, tail4, tail5, tail6, tail7, tail8, tail9,
-                    tail_visited, 
-                    (head.0, head.1 - 1), 
-                    amount,
-                )
+/// Pulls a rope of `knots` knots through every move.
+pub fn simulate<P: Coords>(
+    moves: &[Move],
+    knots: usize,
+    physics: Physics,
+) -> anyhow::Result<Rope<P>> {
+    let mut rope = Rope::new(knots, physics);
+    for m in moves.iter().copied() {
+        rope.apply(m)?;
+    }
+    Ok(rope)
+}
+
+pub fn part1(moves: &[Move]) -> anyhow::Result<usize> {
+    Ok(simulate::<Point>(moves, 2, Physics::default())?
+        .visited(1)
+        .len())
+}
+
+pub fn part2(moves: &[Move]) -> anyhow::Result<usize> {
+    Ok(simulate::<Point>(moves, 10, Physics::default())?
+        .visited(9)
+        .len())
+}
+
+pub struct Day9;
+
+impl Solution for Day9 {
+    fn day(&self) -> usize {
+        9
+    }
+
+    fn title(&self) -> &'static str {
+        "Rope Bridge"
+    }
+
+    fn parts(&self) -> &'static [&'static str] {
+        &[
+            "Positions visited by the tail of a 2-knot rope",
+            "Positions visited by the tail of a 10-knot rope",
+        ]
+    }
+
+    fn parse(&self, input: &str) -> anyhow::Result<Parsed> {
+        Ok(Box::new(parse(input)?))
+    }
+
+    fn solve_parsed<'a>(
+        &'a self,
+        part: usize,
+        parsed: &'a Parsed,
+    ) -> BoxFuture<'a, anyhow::Result<Answer>> {
+        Box::pin(async move {
+            let moves = solution::downcast::<Vec<Move>>(parsed)?;
+            match part {
+                1 => part1(moves).map(Answer::from),
+                2 => part2(moves).map(Answer::from),
+                _ => Err(solution::no_such_part(self, part)),
             }
-        }
+        })
     }
-    Ok(tail_visited.len())
 }
@@ -0,0 +1,264 @@
+use std::fmt;
+use std::ops::{Index, IndexMut};
+use std::str::FromStr;
+
+use crate::parse::ParseError;
+
+/// Offsets to the orthogonal neighbours of a cell, in reading order.
+const NEIGHBOURS_4: [(isize, isize); 4] = [(-1, 0), (0, -1), (0, 1), (1, 0)];
+
+/// Offsets to the orthogonal and diagonal neighbours of a cell, in reading order.
+const NEIGHBOURS_8: [(isize, isize); 8] = [
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+    (0, -1),
+    (0, 1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+];
+
+/// A rectangular grid of cells, addressed by `(row, column)` from the top left.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    /// Row-major.
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    /// Builds a `width` by `height` grid by calling `f` with each position.
+    pub fn from_fn(width: usize, height: usize, mut f: impl FnMut((usize, usize)) -> T) -> Self {
+        let mut cells = Vec::with_capacity(width * height);
+        for row in 0..height {
+            for col in 0..width {
+                cells.push(f((row, col)));
+            }
+        }
+        Self {
+            width,
+            height,
+            cells,
+        }
+    }
+
+    /// Parses a character map with one row per line, converting each character
+    /// with `cell`. Fails on characters `cell` rejects, which should be
+    /// described by `expected`, and on rows of differing lengths.
+    pub fn parse(
+        source: &str,
+        expected: &str,
+        mut cell: impl FnMut(char) -> Option<T>,
+    ) -> Result<Self, ParseError> {
+        let mut width = None;
+        let mut height = 0;
+        let mut cells = vec![];
+        for line in source.lines() {
+            let mut len = 0;
+            for (i, c) in line.char_indices() {
+                let c = cell(c)
+                    .ok_or_else(|| ParseError::at(source, &line[i..i + c.len_utf8()], expected))?;
+                cells.push(c);
+                len += 1;
+            }
+            match width {
+                None => width = Some(len),
+                Some(width) if width != len => {
+                    return Err(ParseError::at(
+                        source,
+                        line,
+                        format!("a row of {} cells", width),
+                    ));
+                }
+                Some(_) => {}
+            }
+            height += 1;
+        }
+        Ok(Self {
+            width: width.unwrap_or(0),
+            height,
+            cells,
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Whether `(row, col)` is inside the grid.
+    pub fn contains(&self, (row, col): (usize, usize)) -> bool {
+        row < self.height && col < self.width
+    }
+
+    pub fn get(&self, pos: (usize, usize)) -> Option<&T> {
+        if self.contains(pos) {
+            Some(&self.cells[pos.0 * self.width + pos.1])
+        } else {
+            None
+        }
+    }
+
+    pub fn get_mut(&mut self, pos: (usize, usize)) -> Option<&mut T> {
+        if self.contains(pos) {
+            Some(&mut self.cells[pos.0 * self.width + pos.1])
+        } else {
+            None
+        }
+    }
+
+    /// Every cell in row-major order.
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.cells.iter()
+    }
+
+    /// Every position in row-major order.
+    pub fn positions(&self) -> impl Iterator<Item = (usize, usize)> {
+        let width = self.width;
+        (0..self.height).flat_map(move |row| (0..width).map(move |col| (row, col)))
+    }
+
+    /// Every cell with its position, in row-major order.
+    pub fn enumerate(&self) -> impl Iterator<Item = ((usize, usize), &T)> {
+        self.positions().zip(self.cells.iter())
+    }
+
+    /// The positions above, left, right and below `pos` that are inside the grid.
+    pub fn neighbours4(&self, pos: (usize, usize)) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.offsets(pos, &NEIGHBOURS_4)
+    }
+
+    /// The up to eight positions surrounding `pos` that are inside the grid.
+    pub fn neighbours8(&self, pos: (usize, usize)) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.offsets(pos, &NEIGHBOURS_8)
+    }
+
+    fn offsets<'a>(
+        &'a self,
+        (row, col): (usize, usize),
+        offsets: &'static [(isize, isize)],
+    ) -> impl Iterator<Item = (usize, usize)> + 'a {
+        offsets.iter().filter_map(move |(dr, dc)| {
+            let pos = (row.checked_add_signed(*dr)?, col.checked_add_signed(*dc)?);
+            self.contains(pos).then_some(pos)
+        })
+    }
+
+    /// The cells of row `row`, left to right.
+    ///
+    /// Panics if `row` is out of bounds.
+    pub fn row(&self, row: usize) -> &[T] {
+        assert!(row < self.height, "row {} is out of bounds", row);
+        &self.cells[row * self.width..(row + 1) * self.width]
+    }
+
+    /// The cells of column `col`, top to bottom.
+    ///
+    /// Panics if `col` is out of bounds.
+    pub fn column(&self, col: usize) -> impl Iterator<Item = &T> {
+        assert!(col < self.width, "column {} is out of bounds", col);
+        self.cells.iter().skip(col).step_by(self.width)
+    }
+
+    /// Every row, top to bottom.
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        (0..self.height).map(|row| self.row(row))
+    }
+
+    /// Every column, left to right.
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        (0..self.width).map(|col| self.column(col))
+    }
+
+    /// A grid of the same shape with every cell converted by `f`.
+    pub fn map<U>(&self, mut f: impl FnMut((usize, usize), &T) -> U) -> Grid<U> {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.enumerate().map(|(pos, cell)| f(pos, cell)).collect(),
+        }
+    }
+}
+
+impl<T: Clone> Grid<T> {
+    /// A `width` by `height` grid with every cell set to `value`.
+    pub fn new(width: usize, height: usize, value: T) -> Self {
+        Self {
+            width,
+            height,
+            cells: vec![value; width * height],
+        }
+    }
+
+    /// Mirrors the grid along its main diagonal, so rows become columns.
+    pub fn transpose(&self) -> Self {
+        Self::from_fn(self.height, self.width, |(row, col)| {
+            self[(col, row)].clone()
+        })
+    }
+
+    /// Rotates the grid a quarter turn clockwise, so the left column becomes
+    /// the top row.
+    pub fn rotate_clockwise(&self) -> Self {
+        Self::from_fn(self.height, self.width, |(row, col)| {
+            self[(self.height - 1 - col, row)].clone()
+        })
+    }
+
+    /// Rotates the grid a quarter turn counterclockwise, so the right column
+    /// becomes the top row.
+    pub fn rotate_counterclockwise(&self) -> Self {
+        Self::from_fn(self.height, self.width, |(row, col)| {
+            self[(col, self.width - 1 - row)].clone()
+        })
+    }
+}
+
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, pos: (usize, usize)) -> &Self::Output {
+        self.get(pos).unwrap_or_else(|| {
+            panic!(
+                "{:?} is out of bounds of a {}x{} grid",
+                pos, self.width, self.height
+            )
+        })
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+    fn index_mut(&mut self, pos: (usize, usize)) -> &mut Self::Output {
+        let (width, height) = (self.width, self.height);
+        self.get_mut(pos)
+            .unwrap_or_else(|| panic!("{:?} is out of bounds of a {}x{} grid", pos, width, height))
+    }
+}
+
+impl FromStr for Grid<char> {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s, "a character", Some)
+    }
+}
+
+/// Draws one row per line with no separators, the same way it would be parsed.
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, row) in self.rows().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            for cell in row {
+                write!(f, "{}", cell)?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,65 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::Context as _;
+
+/// The input root checked into this repository, holding a `dayN/` directory
+/// of inputs for each day.
+pub const DEFAULT_ROOT: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/input");
+
+/// The input used when none is named: the personal puzzle input.
+pub const DEFAULT_NAME: &str = "main";
+
+/// The directory holding the inputs for `day`.
+pub fn day_dir(root: impl AsRef<Path>, day: usize) -> PathBuf {
+    root.as_ref().join(format!("day{}", day))
+}
+
+/// Every `.txt` input in `dir` as (input name, path), sorted by name. The
+/// input name is the file stem, such as `sample1`.
+pub fn list(dir: impl AsRef<Path>) -> anyhow::Result<Vec<(String, PathBuf)>> {
+    let dir = dir.as_ref();
+    let entries =
+        std::fs::read_dir(dir).with_context(|| format!("Could not read {}.", dir.display()))?;
+    let mut inputs = vec![];
+    for entry in entries {
+        let path = entry?.path();
+        if path.extension().is_some_and(|ext| ext == "txt") {
+            let name = path.file_stem().unwrap().to_string_lossy().into_owned();
+            inputs.push((name, path));
+        }
+    }
+    inputs.sort();
+    Ok(inputs)
+}
+
+/// The path of the input called `name` for `day`, i.e. `root/dayN/name.txt`.
+///
+/// Fails with the names of the day's available inputs if there is no such
+/// input.
+pub fn resolve(root: impl AsRef<Path>, day: usize, name: &str) -> anyhow::Result<PathBuf> {
+    let dir = day_dir(root, day);
+    let path = dir.join(format!("{}.txt", name));
+    if path.is_file() {
+        return Ok(path);
+    }
+
+    let available = list(&dir)
+        .with_context(|| format!("There is no input `{}` for day {}.", name, day))?
+        .into_iter()
+        .map(|(name, _)| name)
+        .collect::<Vec<_>>();
+    if available.is_empty() {
+        anyhow::bail!(
+            "There is no input `{}` for day {}; {} has no inputs.",
+            name,
+            day,
+            dir.display(),
+        );
+    }
+    anyhow::bail!(
+        "There is no input `{}` for day {}; available inputs are: {}.",
+        name,
+        day,
+        available.join(", "),
+    )
+}
@@ -1,12 +1,15 @@
-// For day5.
+// For answer.
 #![feature(decl_macro)]
 // For day3.
 #![feature(iter_array_chunks)]
-// For day5 and day8.
-#![feature(result_flattening)]
-// For day5.
-#![feature(string_leak)]
 
+// So `#[derive(AocParse)]` can name this crate from inside it.
+extern crate self as aoc2022;
+
+pub mod all;
+pub mod answer;
+pub mod bench;
+pub mod cpu;
 pub mod day1;
 pub mod day10;
 pub mod day11;
@@ -18,3 +21,16 @@ pub mod day5;
 pub mod day6;
 pub mod day8;
 pub mod day9;
+pub mod grid;
+pub mod input;
+pub mod parse;
+pub mod point;
+pub mod search;
+pub mod solution;
+
+pub use answer::Answer;
+pub use grid::Grid;
+pub use aoc2022_derive::AocParse;
+pub use parse::ParseError;
+pub use point::{Direction, Point, Vec2};
+pub use solution::{Solution, SOLUTIONS};
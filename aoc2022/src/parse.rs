@@ -0,0 +1,168 @@
+use std::fmt;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// A puzzle input that could not be parsed, and where it went wrong.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// The input file, once the error has made it back to whoever read it.
+    pub file: Option<PathBuf>,
+    /// The 1-indexed line of the input.
+    pub line: usize,
+    /// The 0-indexed byte columns of the offending text within the line.
+    pub columns: Range<usize>,
+    /// What the parser was looking for, e.g. "`addx <int>` or `noop`".
+    pub expected: String,
+    /// The offending text; empty if the line ended too early.
+    pub found: String,
+}
+
+impl ParseError {
+    /// Builds an error pointing at `found`, which must be a subslice of
+    /// `source`. Lines and columns are counted from the start of `source`.
+    pub fn at(source: &str, found: &str, expected: impl Into<String>) -> Self {
+        let offset = offset_of(source, found);
+        let line_start = source[..offset].rfind('\n').map_or(0, |i| i + 1);
+        let column = offset - line_start;
+        Self {
+            file: None,
+            line: source[..offset].matches('\n').count() + 1,
+            columns: column..(column + found.len()),
+            expected: expected.into(),
+            found: found.to_string(),
+        }
+    }
+
+    /// Points just past the end of `line`, for input that ended too early.
+    pub fn after(source: &str, line: &str, expected: impl Into<String>) -> Self {
+        Self::at(source, &line[line.len()..], expected)
+    }
+
+    /// Moves an error down by `lines`, e.g. when it came from parsing a
+    /// single line or chunk that starts partway through the input.
+    pub fn offset_lines(mut self, lines: usize) -> Self {
+        self.line += lines;
+        self
+    }
+
+    /// Records the file the input was read from.
+    pub fn in_file(mut self, file: impl AsRef<Path>) -> Self {
+        self.file = Some(file.as_ref().to_path_buf());
+        self
+    }
+
+    /// Renders the error followed by the offending line of `source`, with
+    /// carets under the offending columns:
+    ///
+    /// ```text
+    /// input.txt:3:6: expected an integer, found `x`
+    ///   |
+    /// 3 | addx x
+    ///   |      ^ expected an integer
+    /// ```
+    pub fn render(&self, source: &str) -> String {
+        let Some(line) = source.lines().nth(self.line - 1) else {
+            return self.to_string();
+        };
+        // Columns are bytes, but carets line up with characters.
+        let width = |range: Range<usize>| line.get(range).map_or(0, |s| s.chars().count());
+        let indent = width(0..self.columns.start);
+        let carets = width(self.columns.clone()).max(1);
+        let gutter = " ".repeat(self.line.to_string().len());
+        format!(
+            "{}\n{} |\n{} | {}\n{} | {}{} expected {}",
+            self,
+            gutter,
+            self.line,
+            line,
+            gutter,
+            " ".repeat(indent),
+            "^".repeat(carets),
+            self.expected,
+        )
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(file) = &self.file {
+            write!(f, "{}:", file.display())?;
+        }
+        write!(
+            f,
+            "{}:{}: expected {}, found ",
+            self.line,
+            self.columns.start + 1,
+            self.expected,
+        )?;
+        if self.found.is_empty() {
+            write!(f, "end of line")
+        } else {
+            write!(f, "`{}`", self.found)
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// The byte offset of `sub` within `source`.
+///
+/// Panics if `sub` is not a subslice of `source`.
+fn offset_of(source: &str, sub: &str) -> usize {
+    let offset = (sub.as_ptr() as usize).wrapping_sub(source.as_ptr() as usize);
+    assert!(
+        offset <= source.len() && offset + sub.len() <= source.len(),
+        "{:?} is not a subslice of the source",
+        sub,
+    );
+    offset
+}
+
+/// The 0-indexed line of `source` that the subslice `sub` starts on.
+pub fn line_index(source: &str, sub: &str) -> usize {
+    source[..offset_of(source, sub)].matches('\n').count()
+}
+
+/// Attaches `file` to the `ParseError` inside `err`, if there is one.
+pub fn in_file(mut err: anyhow::Error, file: impl AsRef<Path>) -> anyhow::Error {
+    if let Some(parse_error) = err.downcast_mut::<ParseError>() {
+        parse_error.file = Some(file.as_ref().to_path_buf());
+    }
+    err
+}
+
+/// Strips `text` from the start of `rest`, for `#[derive(AocParse)]`.
+pub fn literal<'a>(rest: &'a str, text: &str) -> Option<&'a str> {
+    rest.strip_prefix(text)
+}
+
+/// Splits `rest` just before the first `until`, or at its end if `until` is
+/// `None`, for `#[derive(AocParse)]`.
+pub fn field<'a>(rest: &'a str, until: Option<&str>) -> Option<(&'a str, &'a str)> {
+    match until {
+        Some(until) => rest.find(until).map(|i| rest.split_at(i)),
+        None => Some((rest, &rest[rest.len()..])),
+    }
+}
+
+/// Joins the ways a line could have been written into an `expected` for
+/// [`ParseError`]: "`a`", "`a` or `b`", "`a`, `b` or `c`" and so on.
+pub fn one_of<S: AsRef<str>>(alternatives: &[S]) -> String {
+    let alternatives = alternatives
+        .iter()
+        .map(|alternative| alternative.as_ref())
+        .collect::<Vec<_>>();
+    match alternatives.as_slice() {
+        [] => String::from("nothing"),
+        [only] => only.to_string(),
+        [init @ .., last] => format!("{} or {}", init.join(", "), last),
+    }
+}
+
+/// Parses `text`, a subslice of `source`, into a `type_name`, for
+/// `#[derive(AocParse)]`.
+pub fn value<T: FromStr>(source: &str, text: &str, type_name: &str) -> Result<T, ParseError> {
+    text.parse()
+        .map_err(|_| ParseError::at(source, text, format!("a valid `{}`", type_name)))
+}
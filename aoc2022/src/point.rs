@@ -0,0 +1,242 @@
+use std::fmt;
+use std::hash::Hash;
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+
+/// A position or offset on an unbounded 2D plane. `x` grows to the right and
+/// `y` grows downwards, matching the rows and columns of a [`Grid`](crate::Grid).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Point {
+    pub x: i64,
+    pub y: i64,
+}
+
+/// For when a [`Point`] is used as an offset rather than a position.
+pub type Vec2 = Point;
+
+impl Point {
+    pub const ORIGIN: Self = Self::new(0, 0);
+
+    pub const fn new(x: i64, y: i64) -> Self {
+        Self { x, y }
+    }
+
+    /// The number of orthogonal steps between `self` and `other`.
+    pub fn manhattan(self, other: Self) -> u64 {
+        self.x.abs_diff(other.x) + self.y.abs_diff(other.y)
+    }
+
+    /// The number of orthogonal or diagonal steps between `self` and `other`.
+    pub fn chebyshev(self, other: Self) -> u64 {
+        self.x.abs_diff(other.x).max(self.y.abs_diff(other.y))
+    }
+
+    /// Each coordinate clamped to -1, 0 or 1.
+    pub fn signum(self) -> Self {
+        Self::new(self.x.signum(), self.y.signum())
+    }
+
+    /// One orthogonal or diagonal step from `self` towards `target`, or `self`
+    /// if they are the same.
+    pub fn step_towards(self, target: Self) -> Self {
+        self + (target - self).signum()
+    }
+}
+
+impl Add for Point {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self::new(self.x + rhs.x, self.y + rhs.y)
+    }
+}
+
+impl AddAssign for Point {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sub for Point {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self::new(self.x - rhs.x, self.y - rhs.y)
+    }
+}
+
+impl SubAssign for Point {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl Neg for Point {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self::new(-self.x, -self.y)
+    }
+}
+
+impl Mul<i64> for Point {
+    type Output = Self;
+
+    fn mul(self, rhs: i64) -> Self::Output {
+        Self::new(self.x * rhs, self.y * rhs)
+    }
+}
+
+/// A grid position, `(row, column)`.
+impl From<(usize, usize)> for Point {
+    fn from((row, col): (usize, usize)) -> Self {
+        Self::new(col as i64, row as i64)
+    }
+}
+
+/// A grid position, `(row, column)`, if neither coordinate is negative.
+impl TryFrom<Point> for (usize, usize) {
+    type Error = std::num::TryFromIntError;
+
+    fn try_from(point: Point) -> Result<Self, Self::Error> {
+        Ok((usize::try_from(point.y)?, usize::try_from(point.x)?))
+    }
+}
+
+/// Integer coordinates with a fixed number of axes, for code that works the
+/// same in any number of dimensions. Implemented by [`Point`] and by arrays
+/// such as `[i64; 3]`.
+pub trait Coords: Copy + Eq + Hash + fmt::Debug {
+    const DIMENSIONS: usize;
+
+    /// Builds coordinates from the value along each axis in turn.
+    fn from_fn(f: impl FnMut(usize) -> i64) -> Self;
+
+    /// The value along `axis`, counting from 0.
+    fn axis(self, axis: usize) -> i64;
+
+    fn origin() -> Self {
+        Self::from_fn(|_| 0)
+    }
+
+    /// Adds `offset` along every axis.
+    fn translate(self, offset: Self) -> Self {
+        Self::from_fn(|axis| self.axis(axis) + offset.axis(axis))
+    }
+}
+
+/// `x` is axis 0 and `y` is axis 1.
+impl Coords for Point {
+    const DIMENSIONS: usize = 2;
+
+    fn from_fn(mut f: impl FnMut(usize) -> i64) -> Self {
+        Self::new(f(0), f(1))
+    }
+
+    fn axis(self, axis: usize) -> i64 {
+        match axis {
+            0 => self.x,
+            1 => self.y,
+            _ => panic!("a Point has no axis {}", axis),
+        }
+    }
+}
+
+impl<const N: usize> Coords for [i64; N] {
+    const DIMENSIONS: usize = N;
+
+    fn from_fn(f: impl FnMut(usize) -> i64) -> Self {
+        std::array::from_fn(f)
+    }
+
+    fn axis(self, axis: usize) -> i64 {
+        self[axis]
+    }
+}
+
+/// One of the four orthogonal directions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+impl Direction {
+    /// Clockwise from `Up`.
+    pub const ALL: [Self; 4] = [Self::Up, Self::Right, Self::Down, Self::Left];
+
+    /// A single step in this direction.
+    pub fn offset(self) -> Vec2 {
+        match self {
+            Self::Up => Vec2::new(0, -1),
+            Self::Right => Vec2::new(1, 0),
+            Self::Down => Vec2::new(0, 1),
+            Self::Left => Vec2::new(-1, 0),
+        }
+    }
+
+    pub fn turn_right(self) -> Self {
+        Self::ALL[(self as usize + 1) % 4]
+    }
+
+    pub fn turn_left(self) -> Self {
+        Self::ALL[(self as usize + 3) % 4]
+    }
+
+    pub fn opposite(self) -> Self {
+        Self::ALL[(self as usize + 2) % 4]
+    }
+}
+
+/// One of the four orthogonal or four diagonal directions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction8 {
+    Up,
+    UpRight,
+    Right,
+    DownRight,
+    Down,
+    DownLeft,
+    Left,
+    UpLeft,
+}
+
+impl Direction8 {
+    /// Clockwise from `Up`.
+    pub const ALL: [Self; 8] = [
+        Self::Up,
+        Self::UpRight,
+        Self::Right,
+        Self::DownRight,
+        Self::Down,
+        Self::DownLeft,
+        Self::Left,
+        Self::UpLeft,
+    ];
+
+    /// A single step in this direction.
+    pub fn offset(self) -> Vec2 {
+        match self {
+            Self::Up => Vec2::new(0, -1),
+            Self::UpRight => Vec2::new(1, -1),
+            Self::Right => Vec2::new(1, 0),
+            Self::DownRight => Vec2::new(1, 1),
+            Self::Down => Vec2::new(0, 1),
+            Self::DownLeft => Vec2::new(-1, 1),
+            Self::Left => Vec2::new(-1, 0),
+            Self::UpLeft => Vec2::new(-1, -1),
+        }
+    }
+}
+
+impl From<Direction> for Direction8 {
+    fn from(direction: Direction) -> Self {
+        match direction {
+            Direction::Up => Self::Up,
+            Direction::Right => Self::Right,
+            Direction::Down => Self::Down,
+            Direction::Left => Self::Left,
+        }
+    }
+}
@@ -0,0 +1,209 @@
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::hash::Hash;
+use std::ops::Add;
+
+/// The result of a search: how far each reached node is from the nearest
+/// start, and the predecessor tree for walking back to it.
+#[derive(Debug, Clone)]
+pub struct Search<N, C> {
+    distances: HashMap<N, C>,
+    predecessors: HashMap<N, N>,
+    goal: Option<N>,
+}
+
+impl<N: Clone + Eq + Hash, C: Copy> Search<N, C> {
+    fn new() -> Self {
+        Self {
+            distances: HashMap::new(),
+            predecessors: HashMap::new(),
+            goal: None,
+        }
+    }
+
+    /// The distance to `node` from the nearest start. Exact for every node
+    /// the search settled before it stopped at a goal, and for every node if
+    /// it never did.
+    pub fn distance(&self, node: &N) -> Option<C> {
+        self.distances.get(node).copied()
+    }
+
+    /// Every node the search reached, with its distance from the nearest start.
+    pub fn distances(&self) -> &HashMap<N, C> {
+        &self.distances
+    }
+
+    /// The node each reached node was first reached from along a shortest
+    /// path. Starts have no predecessor.
+    pub fn predecessors(&self) -> &HashMap<N, N> {
+        &self.predecessors
+    }
+
+    /// The first goal node settled, if any.
+    pub fn goal(&self) -> Option<&N> {
+        self.goal.as_ref()
+    }
+
+    /// The distance to the goal, if one was found.
+    pub fn goal_distance(&self) -> Option<C> {
+        self.goal.as_ref().and_then(|goal| self.distance(goal))
+    }
+
+    /// The nodes along a shortest path from a start to `node`, inclusive.
+    pub fn path_to(&self, node: &N) -> Option<Vec<N>> {
+        if !self.distances.contains_key(node) {
+            return None;
+        }
+        let mut path = vec![node.clone()];
+        while let Some(previous) = self.predecessors.get(path.last().unwrap()) {
+            path.push(previous.clone());
+        }
+        path.reverse();
+        Some(path)
+    }
+
+    /// The nodes along a shortest path from a start to the goal, inclusive.
+    pub fn goal_path(&self) -> Option<Vec<N>> {
+        self.goal.as_ref().and_then(|goal| self.path_to(goal))
+    }
+}
+
+/// Breadth-first search from every node in `starts` at once, where every edge
+/// costs 1. Stops at the first node for which `is_goal` holds; pass `|_| false`
+/// to reach every node.
+pub fn bfs<N, I>(
+    starts: impl IntoIterator<Item = N>,
+    mut neighbours: impl FnMut(&N) -> I,
+    mut is_goal: impl FnMut(&N) -> bool,
+) -> Search<N, usize>
+where
+    N: Clone + Eq + Hash,
+    I: IntoIterator<Item = N>,
+{
+    let mut search = Search::new();
+    let mut queue = VecDeque::new();
+    for start in starts {
+        if search.distances.insert(start.clone(), 0).is_none() {
+            queue.push_back(start);
+        }
+    }
+
+    while let Some(node) = queue.pop_front() {
+        if is_goal(&node) {
+            search.goal = Some(node);
+            break;
+        }
+        let distance = search.distances[&node];
+        for next in neighbours(&node) {
+            if !search.distances.contains_key(&next) {
+                search.distances.insert(next.clone(), distance + 1);
+                search.predecessors.insert(next.clone(), node.clone());
+                queue.push_back(next);
+            }
+        }
+    }
+    search
+}
+
+/// Dijkstra's algorithm from every node in `starts` at once, where
+/// `neighbours` yields each neighbour with the cost of the edge to it. Stops
+/// at the first node for which `is_goal` holds; pass `|_| false` to reach
+/// every node.
+pub fn dijkstra<N, C, I>(
+    starts: impl IntoIterator<Item = N>,
+    neighbours: impl FnMut(&N) -> I,
+    is_goal: impl FnMut(&N) -> bool,
+) -> Search<N, C>
+where
+    N: Clone + Eq + Hash,
+    C: Copy + Ord + Add<Output = C> + Default,
+    I: IntoIterator<Item = (N, C)>,
+{
+    astar(starts, neighbours, |_| C::default(), is_goal)
+}
+
+/// A* search, which is Dijkstra's algorithm guided by `heuristic`, an
+/// estimate of the remaining cost to a goal. The result is only exact if the
+/// heuristic never overestimates.
+pub fn astar<N, C, I>(
+    starts: impl IntoIterator<Item = N>,
+    mut neighbours: impl FnMut(&N) -> I,
+    mut heuristic: impl FnMut(&N) -> C,
+    mut is_goal: impl FnMut(&N) -> bool,
+) -> Search<N, C>
+where
+    N: Clone + Eq + Hash,
+    C: Copy + Ord + Add<Output = C> + Default,
+    I: IntoIterator<Item = (N, C)>,
+{
+    let mut search = Search::new();
+    let mut heap = BinaryHeap::new();
+    for start in starts {
+        if search
+            .distances
+            .insert(start.clone(), C::default())
+            .is_none()
+        {
+            heap.push(Entry {
+                estimate: heuristic(&start),
+                distance: C::default(),
+                node: start,
+            });
+        }
+    }
+
+    while let Some(Entry { distance, node, .. }) = heap.pop() {
+        // Skip entries superseded by a shorter path found since they were pushed.
+        if distance > search.distances[&node] {
+            continue;
+        }
+        if is_goal(&node) {
+            search.goal = Some(node);
+            break;
+        }
+        for (next, cost) in neighbours(&node) {
+            let next_distance = distance + cost;
+            if search
+                .distances
+                .get(&next)
+                .is_none_or(|known| next_distance < *known)
+            {
+                search.distances.insert(next.clone(), next_distance);
+                search.predecessors.insert(next.clone(), node.clone());
+                heap.push(Entry {
+                    estimate: next_distance + heuristic(&next),
+                    distance: next_distance,
+                    node: next,
+                });
+            }
+        }
+    }
+    search
+}
+
+/// A node waiting in the A* heap, ordered so the lowest estimate pops first.
+struct Entry<N, C> {
+    estimate: C,
+    distance: C,
+    node: N,
+}
+
+impl<N, C: Ord> PartialEq for Entry<N, C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.estimate == other.estimate
+    }
+}
+
+impl<N, C: Ord> Eq for Entry<N, C> {}
+
+impl<N, C: Ord> PartialOrd for Entry<N, C> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<N, C: Ord> Ord for Entry<N, C> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.estimate.cmp(&self.estimate)
+    }
+}
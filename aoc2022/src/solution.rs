@@ -0,0 +1,97 @@
+use std::any::Any;
+use std::path::Path;
+
+use anyhow::Context as _;
+use futures::future::BoxFuture;
+
+use crate::answer::Answer;
+use crate::parse;
+
+/// A day's input after parsing. Every part of the day solves from the same
+/// parsed value, so it can be parsed once and timed separately.
+pub type Parsed = Box<dyn Any + Send + Sync>;
+
+/// A single day's puzzle: its metadata and a way to run each of its parts.
+pub trait Solution: Send + Sync {
+    /// The day of the advent calendar this puzzle belongs to.
+    fn day(&self) -> usize;
+
+    /// The title of the puzzle.
+    fn title(&self) -> &'static str;
+
+    /// The names of the puzzle's parts. Part `n` is `parts()[n - 1]`.
+    fn parts(&self) -> &'static [&'static str];
+
+    /// Parses the puzzle `input` into the value every part solves from.
+    fn parse(&self, input: &str) -> anyhow::Result<Parsed>;
+
+    /// Solves the 1-indexed `part` from a value produced by `parse`.
+    fn solve_parsed<'a>(
+        &'a self,
+        part: usize,
+        parsed: &'a Parsed,
+    ) -> BoxFuture<'a, anyhow::Result<Answer>>;
+
+    /// Parses the puzzle `input` and solves the 1-indexed `part` for it.
+    fn solve<'a>(&'a self, part: usize, input: &'a str) -> BoxFuture<'a, anyhow::Result<Answer>> {
+        Box::pin(async move {
+            let parsed = self.parse(input)?;
+            self.solve_parsed(part, &parsed).await
+        })
+    }
+
+    /// Reads the input file at `path` and solves the 1-indexed `part` for it.
+    fn run<'a>(&'a self, part: usize, path: &'a Path) -> BoxFuture<'a, anyhow::Result<Answer>> {
+        Box::pin(async move {
+            let input = tokio::fs::read_to_string(path)
+                .await
+                .with_context(|| format!("Could not read {}.", path.display()))?;
+            self.solve(part, &input)
+                .await
+                .map_err(|err| parse::in_file(err, path))
+        })
+    }
+}
+
+/// The error for a part number that `solution` does not have.
+pub fn no_such_part(solution: &(impl Solution + ?Sized), part: usize) -> anyhow::Error {
+    anyhow::anyhow!(
+        "Day {} has no part {} (it has {}).",
+        solution.day(),
+        part,
+        solution.parts().len(),
+    )
+}
+
+/// Borrows the concrete value that a day's `parse` produced.
+pub fn downcast<T: Any>(parsed: &Parsed) -> anyhow::Result<&T> {
+    parsed.downcast_ref::<T>().ok_or_else(|| {
+        anyhow::anyhow!(
+            "Parsed input is not a {}; was it parsed by another day?",
+            std::any::type_name::<T>(),
+        )
+    })
+}
+
+/// Every registered solution, in day order.
+pub static SOLUTIONS: &[&dyn Solution] = &[
+    &crate::day1::Day1,
+    &crate::day2::Day2,
+    &crate::day3::Day3,
+    &crate::day4::Day4,
+    &crate::day5::Day5,
+    &crate::day6::Day6,
+    &crate::day8::Day8,
+    &crate::day9::Day9,
+    &crate::day10::Day10,
+    &crate::day11::Day11,
+    &crate::day12::Day12,
+];
+
+/// Finds the registered solution for `day`.
+pub fn get(day: usize) -> Option<&'static dyn Solution> {
+    SOLUTIONS
+        .iter()
+        .copied()
+        .find(|solution| solution.day() == day)
+}
@@ -0,0 +1,56 @@
+//! Checks that `aoc all` reports days it cannot read instead of stopping.
+
+use std::fs;
+
+use aoc2022::all::{self, Status};
+use aoc2022::input;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn unreadable_days_fail_without_stopping_the_others() {
+    let root = std::env::temp_dir().join(format!("aoc2022-all-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    let day = |n| input::day_dir(&root, n);
+    fs::create_dir_all(day(1)).unwrap();
+    fs::write(day(1).join("answers.json"), "{ not json").unwrap();
+    fs::create_dir_all(day(2)).unwrap();
+    fs::copy(
+        input::day_dir(input::DEFAULT_ROOT, 2).join("sample.txt"),
+        day(2).join("sample.txt"),
+    )
+    .unwrap();
+    fs::copy(
+        input::day_dir(input::DEFAULT_ROOT, 2).join("answers.json"),
+        day(2).join("answers.json"),
+    )
+    .unwrap();
+
+    let runs = all::run_all(&root).await;
+    fs::remove_dir_all(&root).unwrap();
+
+    let day1 = runs.iter().filter(|run| run.day == 1).collect::<Vec<_>>();
+    assert_eq!(day1.len(), 2);
+    for run in day1 {
+        assert_eq!((run.input.as_str(), run.status()), ("-", Status::Failed));
+        let err = run.result.as_ref().unwrap_err().to_string();
+        assert!(err.starts_with("Could not parse "), "{}", err);
+    }
+
+    let day2 = runs.iter().filter(|run| run.day == 2).collect::<Vec<_>>();
+    assert_eq!(day2.len(), 2);
+    assert!(day2
+        .iter()
+        .all(|run| run.input == "sample" && run.status() == Status::Ok));
+
+    // Every other day's directory is missing.
+    let day3 = runs.iter().find(|run| run.day == 3).unwrap();
+    assert_eq!(day3.status(), Status::Failed);
+    assert!(day3
+        .result
+        .as_ref()
+        .unwrap_err()
+        .to_string()
+        .starts_with("Could not read "));
+
+    let days = runs.iter().map(|run| run.day).collect::<Vec<_>>();
+    assert!(days.windows(2).all(|pair| pair[0] <= pair[1]));
+}
@@ -0,0 +1,49 @@
+//! Runs every part of every registered day against every input under
+//! `input/dayN/` and checks the results against `input/dayN/answers.json`.
+
+use aoc2022::answer::ExpectedAnswers;
+use aoc2022::{input, SOLUTIONS};
+
+#[tokio::test(flavor = "multi_thread")]
+async fn every_input_matches_its_recorded_answers() {
+    let mut checked = 0;
+    let mut failures = vec![];
+    for solution in SOLUTIONS {
+        let dir = input::day_dir(input::DEFAULT_ROOT, solution.day());
+        let expected = match ExpectedAnswers::load(&dir) {
+            Ok(expected) => expected,
+            Err(err) => {
+                failures.push(format!("day {}: {:#}", solution.day(), err));
+                continue;
+            }
+        };
+
+        for (input, path) in input::list(&dir).unwrap() {
+            for part in 1..=solution.parts().len() {
+                let label = format!("day {} part {} on {}", solution.day(), part, input);
+                let Some(expected) = expected.get(&input, part) else {
+                    failures.push(format!("{}: no recorded answer", label));
+                    continue;
+                };
+                checked += 1;
+                match solution.run(part, &path).await {
+                    Ok(actual) if actual == *expected => {}
+                    Ok(actual) => failures.push(format!(
+                        "{}: expected {:?}, got {:?}",
+                        label, expected, actual
+                    )),
+                    Err(err) => failures.push(format!("{}: failed with {:#}", label, err)),
+                }
+            }
+        }
+    }
+
+    assert!(checked > 0, "No answers were checked.");
+    assert!(
+        failures.is_empty(),
+        "Found {} problem(s) while checking {} answers:\n{}",
+        failures.len(),
+        checked,
+        failures.join("\n"),
+    );
+}
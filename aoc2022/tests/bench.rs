@@ -0,0 +1,68 @@
+//! Checks how benchmark timings are summarized and compared to a baseline.
+
+use std::time::Duration;
+
+use aoc2022::bench::{self, Baseline, Measurement, Phase, Regression, Stats};
+
+fn stats(millis: &[u64]) -> Stats {
+    Stats::new(millis.iter().copied().map(Duration::from_millis).collect())
+}
+
+fn measurement(day: usize, part: usize, parse: &[u64], solve: &[u64]) -> Measurement {
+    Measurement {
+        day,
+        part,
+        iterations: parse.len(),
+        parse: stats(parse),
+        solve: stats(solve),
+    }
+}
+
+#[test]
+fn stats_pick_the_middle_timing_whatever_the_order() {
+    let ms = Duration::from_millis;
+    let odd = stats(&[30, 10, 50, 20, 40]);
+    assert_eq!(
+        (odd.min(), odd.median(), odd.max()),
+        (ms(10), ms(30), ms(50))
+    );
+    let even = stats(&[40, 10, 30, 20]);
+    assert_eq!(
+        (even.min(), even.median(), even.max()),
+        (ms(10), ms(30), ms(40))
+    );
+}
+
+#[test]
+fn only_medians_slower_than_the_threshold_regress() {
+    let baseline = Baseline {
+        measurements: vec![
+            measurement(1, 1, &[100], &[100]),
+            measurement(1, 2, &[100], &[100]),
+        ],
+    };
+    let current = Baseline {
+        measurements: vec![
+            // 9% slower to parse is within 10%, but 11% slower to solve isn't,
+            // even though the slowest solve is far quicker than before.
+            measurement(1, 1, &[1, 109, 500], &[50, 111, 111]),
+            measurement(1, 2, &[90], &[100]),
+            // Nothing to compare against.
+            measurement(2, 1, &[1000], &[1000]),
+        ],
+    };
+
+    let regressions = bench::regressions(&baseline, &current, 0.1);
+    assert_eq!(
+        regressions,
+        [Regression {
+            day: 1,
+            part: 1,
+            phase: Phase::Solve,
+            baseline: Duration::from_millis(100),
+            current: Duration::from_millis(111),
+        }]
+    );
+    assert!((regressions[0].slowdown() - 0.11).abs() < 1e-9);
+    assert!(bench::regressions(&baseline, &current, 0.2).is_empty());
+}
@@ -0,0 +1,121 @@
+//! Checks the cycle-accurate CPU from day 10 and opcodes added to it.
+
+use aoc2022::cpu::{Cpu, InstructionSet, Opcode, State};
+use aoc2022::day10;
+
+/// Day 10's instructions plus `addy`, `mulx` and a relative `jmp`.
+fn extended() -> InstructionSet {
+    day10::instruction_set()
+        .with(Opcode {
+            name: "addy",
+            args: 1,
+            cycles: 2,
+            effect: |state, args| {
+                state.set_register('y', state.register('y') + args[0]);
+                Ok(())
+            },
+        })
+        .with(Opcode {
+            name: "mulx",
+            args: 1,
+            cycles: 3,
+            effect: |state, args| {
+                state.set_register('x', state.register('x') * args[0]);
+                Ok(())
+            },
+        })
+        .with(Opcode {
+            name: "jmp",
+            args: 1,
+            cycles: 1,
+            effect: |state, args| {
+                state.ip = (state.ip - 1)
+                    .checked_add_signed(args[0])
+                    .ok_or_else(|| anyhow::anyhow!("Jumped before the first instruction"))?;
+                Ok(())
+            },
+        })
+}
+
+/// The `(cycle, x, y)` during every cycle until the program halts or `limit`.
+fn trace(source: &str, limit: usize) -> anyhow::Result<Vec<(usize, isize, isize)>> {
+    let program = extended().parse(source)?;
+    let mut cpu = Cpu::new(&program, State::new([('x', 1)]));
+    let mut trace = vec![];
+    cpu.run(limit, |cycle, state| {
+        trace.push((cycle, state.register('x'), state.register('y')))
+    })?;
+    Ok(trace)
+}
+
+#[test]
+fn registers_change_only_after_an_instruction_finishes() -> anyhow::Result<()> {
+    let program = day10::parse("noop\naddx 3\naddx -5")?;
+    let mut cpu = Cpu::new(&program, State::new([('x', 1)]));
+    let mut xs = vec![];
+    cpu.run(usize::MAX, |_, state| xs.push(state.register('x')))?;
+    assert_eq!(xs, [1, 1, 1, 4, 4]);
+    assert!(cpu.halted());
+    assert_eq!(cpu.cycle(), 5);
+    assert_eq!(cpu.state().register('x'), -1);
+    Ok(())
+}
+
+#[test]
+fn new_opcodes_use_their_own_cycle_costs_and_registers() -> anyhow::Result<()> {
+    assert_eq!(
+        trace("addy 4\nmulx 3\nnoop", 100)?,
+        [
+            (1, 1, 0),
+            (2, 1, 0),
+            (3, 1, 4),
+            (4, 1, 4),
+            (5, 1, 4),
+            (6, 3, 4),
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn jumps_move_the_instruction_pointer() -> anyhow::Result<()> {
+    // Skips `addx 10`, then loops on `addx 1` until stopped.
+    let trace = trace("jmp 2\naddx 10\naddx 1\njmp -1", 9)?;
+    let xs = trace.iter().map(|(_, x, _)| *x).collect::<Vec<_>>();
+    assert_eq!(xs, [1, 1, 1, 2, 2, 2, 3, 3, 3]);
+
+    let error = self::trace("noop\njmp -5", 10).unwrap_err();
+    assert_eq!(error.to_string(), "Jumped before the first instruction");
+    Ok(())
+}
+
+#[test]
+fn ticking_a_halted_program_does_nothing() -> anyhow::Result<()> {
+    let program = day10::parse("noop")?;
+    let mut cpu = Cpu::new(&program, State::default());
+    let mut hook = |_, _: &State| {};
+    assert!(cpu.tick(&mut hook)?);
+    assert!(!cpu.tick(&mut hook)?);
+    assert_eq!(cpu.cycle(), 1);
+    Ok(())
+}
+
+#[test]
+fn unknown_opcodes_list_every_known_one() {
+    let error = extended().parse("noop\nsubx 2").unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "2:1: expected `addx <isize>`, `noop`, `addy <isize>`, `mulx <isize>` or `jmp <isize>`, found `subx 2`"
+    );
+}
+
+#[test]
+fn programs_that_halt_early_are_errors() -> anyhow::Result<()> {
+    let program = day10::parse("noop\naddx 1")?;
+    let error = day10::part1(&program).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "The program halted after 3 cycles, before cycle 220"
+    );
+    Ok(())
+}
@@ -0,0 +1,300 @@
+//! Checks day 11's parser on notes laid out differently from the puzzle's
+//! own, and its relief strategies, overflow checks, cycle detection and
+//! reports.
+
+use aoc2022::day11::{self, DivideBy, ItemId, Modulo, NoRelief, RoundStats, Throw};
+use num_bigint::BigUint;
+
+const SAMPLE: &str = include_str!("../input/day11/sample.txt");
+
+#[test]
+fn monkeys_parse_with_crlf_extra_whitespace_and_reordered_lines() {
+    let reordered = "Monkey 0 :\r
+  Operation:   new = old*19\r
+  Starting items: 79,98\r
+\tIf false: throw to monkey 3\r
+  Test: divisible by   23\r
+  If true: throw to monkey 2\r
+\r
+\r
+Monkey 1:\r
+Starting items: 54, 65, 75, 74\r
+Operation: new = old + 6\r
+Test: divisible by 19\r
+If true: throw to monkey 2\r
+If false: throw to monkey 0\r
+Monkey 2:\r
+  Starting items: 79, 60, 97\r
+  Operation: new = old * old\r
+  Test: divisible by 13\r
+    If false: throw to monkey 3\r
+    If true: throw to monkey 1\r
+\r
+Monkey 3:\r
+  Starting items: 74\r
+  Operation: new = old + 3\r
+  Test: divisible by 17\r
+    If true: throw to monkey 0\r
+    If false: throw to monkey 1\r
+";
+    let monkeys = day11::parse(reordered).unwrap();
+    assert_eq!(day11::part1(&monkeys).unwrap(), 10605);
+    assert_eq!(day11::part2(&monkeys).unwrap(), 2713310158);
+}
+
+/// The sample followed by monkeys 4 to 10, which hold nothing and are never
+/// thrown to, but throw to `target` themselves.
+fn with_idle_monkeys(target: usize) -> String {
+    let mut input = SAMPLE.to_string();
+    for id in 4..=10 {
+        input.push_str(&format!(
+            "\n\nMonkey {}:\n  Starting items:\n  Operation: new = old + 1\n  Test: divisible by 2\n    If true: throw to monkey {}\n    If false: throw to monkey 0",
+            id, target,
+        ));
+    }
+    input
+}
+
+#[test]
+fn monkeys_parse_with_multi_digit_ids_and_targets() {
+    let monkeys = day11::parse(&with_idle_monkeys(10)).unwrap();
+    assert_eq!(day11::part1(&monkeys).unwrap(), 10605);
+}
+
+#[test]
+fn throw_targets_must_be_existing_monkeys() {
+    let input = with_idle_monkeys(11);
+    let err = day11::parse(&input).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "33:30: expected a monkey from 0 to 10, found `11`"
+    );
+}
+
+#[test]
+fn operations_can_be_any_arithmetic_on_old() {
+    // The same operations as the sample, written the long way round.
+    let rewritten = SAMPLE
+        .replace("old * 19", "(old * 9) + old * 10")
+        .replace("old + 6", "old + 2 * (1 + 2)")
+        .replace("old * old", "old*old")
+        .replace("old + 3", "((old)) + 1 + 2");
+    let monkeys = day11::parse(&rewritten).unwrap();
+    assert_eq!(day11::part1(&monkeys).unwrap(), 10605);
+    assert_eq!(day11::part2(&monkeys).unwrap(), 2713310158);
+}
+
+#[test]
+fn division_is_refused_modulo_the_lcm() {
+    let halved = SAMPLE.replace("old * 19", "old * 38 / 2");
+    let monkeys = day11::parse(&halved).unwrap();
+    assert_eq!(day11::part1(&monkeys).unwrap(), 10605);
+    let err = day11::part2(&monkeys).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Monkey 0's operation uses `/`, which can't be worked out on worry levels reduced modulo 96577: dividing a reduced level doesn't give the same remainder as dividing the real one"
+    );
+
+    // Halving a reduced worry level loses track of the real one, so this
+    // would drift from the exact counts if it were allowed.
+    let inexact = SAMPLE.replace("old * 19", "old * 19 / 2");
+    let monkeys = day11::parse(&inexact).unwrap();
+    assert_eq!(
+        day11::inspection_counts::<BigUint>(&monkeys, 10, &NoRelief).unwrap(),
+        [48, 48, 5, 52]
+    );
+    let modulo = Modulo(monkeys.lcm().unwrap());
+    assert!(day11::inspection_counts::<usize>(&monkeys, 10, &modulo).is_err());
+    assert!(day11::inspection_counts_by_cycles::<usize>(&monkeys, 10, &modulo).is_err());
+    assert!(day11::report::<BigUint>(&monkeys, 10, &modulo, &[]).is_err());
+
+    let zero = SAMPLE.replace("old * 19", "old % (old - old)");
+    let monkeys = day11::parse(&zero).unwrap();
+    let err = day11::part1(&monkeys).unwrap_err();
+    assert_eq!(err.to_string(), "Division by zero in `79 % 0`");
+}
+
+#[test]
+fn relief_can_be_chosen_at_run_time() {
+    let monkeys = day11::parse(SAMPLE).unwrap();
+    let divide_by_3 = |worry: usize| worry / 3;
+    assert_eq!(
+        day11::monkey_business(&monkeys, 20, &divide_by_3).unwrap(),
+        10605
+    );
+    assert_eq!(
+        day11::monkey_business::<usize>(&monkeys, 20, &day11::DivideBy(3)).unwrap(),
+        10605
+    );
+    // Working modulo the LCM, the first 20 rounds are the same as part 2's.
+    let part2_after_20 = 99 * 103;
+    assert_eq!(
+        day11::monkey_business::<usize>(&monkeys, 20, &Modulo(96577)).unwrap(),
+        part2_after_20
+    );
+}
+
+#[test]
+fn lcm_handles_composite_divisors() {
+    let monkeys = day11::parse(&SAMPLE.replace("by 23", "by 4").replace("by 19", "by 6")).unwrap();
+    assert_eq!(monkeys.lcm().unwrap(), 12 * 13 * 17);
+    assert_eq!(day11::parse(SAMPLE).unwrap().lcm().unwrap(), 96577);
+}
+
+#[test]
+fn worry_levels_fail_loudly_instead_of_overflowing() {
+    let monkeys = day11::parse(SAMPLE).unwrap();
+    let err = day11::monkey_business::<usize>(&monkeys, 20, &NoRelief).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Worry level `13988703546165100909 * 19` overflows a `usize`"
+    );
+    assert_eq!(
+        day11::monkey_business::<u128>(&monkeys, 20, &NoRelief).unwrap(),
+        99 * 103
+    );
+
+    let err = day11::monkey_business::<u128>(&monkeys, 40, &NoRelief).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Worry level `234271887539542109550433007260887848071 * 19` overflows a `u128`"
+    );
+    assert_eq!(
+        day11::monkey_business::<BigUint>(&monkeys, 40, &NoRelief).unwrap(),
+        day11::monkey_business::<usize>(&monkeys, 40, &Modulo(96577)).unwrap(),
+    );
+
+    let below_zero = SAMPLE.replace("old + 3", "old - 100");
+    let monkeys = day11::parse(&below_zero).unwrap();
+    let err = day11::part1(&monkeys).unwrap_err();
+    assert_eq!(err.to_string(), "Worry level `74 - 100` goes below zero");
+    // Modulo the LCM, a reduced level could go below zero when the real one
+    // doesn't, so subtraction is refused rather than failing falsely.
+    let modulo = Modulo(monkeys.lcm().unwrap());
+    let err = day11::part2(&monkeys).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Monkey 3's operation uses `-`, which can't be worked out on worry levels reduced modulo 96577: a reduced level can go below zero when the real one doesn't"
+    );
+    let err = day11::monkey_business::<BigUint>(&monkeys, 20, &modulo).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Monkey 3's operation uses `-`, which can't be worked out on worry levels reduced modulo 96577: a reduced level can go below zero when the real one doesn't"
+    );
+}
+
+#[test]
+fn cycle_detection_matches_playing_every_round() {
+    let monkeys = day11::parse(SAMPLE).unwrap();
+    let modulo = Modulo(monkeys.lcm().unwrap());
+    for rounds in [0, 1, 20, 1000, 10_000] {
+        assert_eq!(
+            day11::inspection_counts_by_cycles::<usize>(&monkeys, rounds, &modulo).unwrap(),
+            day11::inspection_counts::<usize>(&monkeys, rounds, &modulo).unwrap(),
+            "{} rounds modulo the LCM",
+            rounds,
+        );
+    }
+    // Divided by 3, worry levels overflow a usize long before 1000 rounds.
+    for rounds in [0, 1, 20] {
+        assert_eq!(
+            day11::inspection_counts_by_cycles::<usize>(&monkeys, rounds, &DivideBy(3)).unwrap(),
+            day11::inspection_counts::<usize>(&monkeys, rounds, &DivideBy(3)).unwrap(),
+            "{} rounds divided by 3",
+            rounds,
+        );
+    }
+}
+
+#[test]
+fn cycle_detection_skips_ahead_through_huge_round_counts() {
+    let monkeys = day11::parse(SAMPLE).unwrap();
+    let modulo = Modulo(monkeys.lcm().unwrap());
+    let counts =
+        day11::inspection_counts_by_cycles::<usize>(&monkeys, 1_000_000_000_000, &modulo).unwrap();
+    // Every item is inspected at least once a round.
+    assert!(counts.iter().sum::<usize>() >= 10 * 1_000_000_000_000);
+
+    // Twice the rounds is twice the inspections, give or take one lap of
+    // each item's cycle.
+    let double =
+        day11::inspection_counts_by_cycles::<usize>(&monkeys, 2_000_000_000_000, &modulo).unwrap();
+    for (once, twice) in counts.iter().zip(double) {
+        assert!(
+            twice.abs_diff(2 * once) < 1_000_000,
+            "{} vs {}",
+            once,
+            twice
+        );
+    }
+}
+
+#[test]
+fn reports_record_every_round_and_traced_items() {
+    let monkeys = day11::parse(SAMPLE).unwrap();
+    let first = ItemId {
+        monkey: 0,
+        index: 0,
+    };
+    let report = day11::report::<usize>(&monkeys, 20, &DivideBy(3), &[first]).unwrap();
+
+    assert_eq!(report.rounds.len(), 20);
+    assert_eq!(
+        report.rounds[0],
+        RoundStats {
+            inspections: vec![2, 4, 3, 5],
+            queue_lengths: vec![4, 6, 0, 0],
+        }
+    );
+    assert_eq!(report.inspection_counts(), vec![101, 95, 7, 105]);
+    assert_eq!(report.busiest(2), vec![(3, 105), (0, 101)]);
+
+    let throw = |round, from, to, worry| Throw {
+        round,
+        from,
+        to,
+        worry,
+    };
+    assert_eq!(
+        report.journeys[&first][..5],
+        [
+            throw(1, 0, 3, 500),
+            throw(1, 3, 1, 167),
+            throw(2, 1, 2, 57),
+            throw(2, 2, 3, 1083),
+            throw(2, 3, 1, 362),
+        ]
+    );
+
+    let err = day11::report::<usize>(
+        &monkeys,
+        20,
+        &DivideBy(3),
+        &[ItemId {
+            monkey: 2,
+            index: 3,
+        }],
+    )
+    .unwrap_err();
+    assert_eq!(err.to_string(), "Monkey 2 has no starting item 3");
+
+    let empty = day11::report::<usize>(&monkeys, 0, &DivideBy(3), &[]).unwrap();
+    assert!(empty.rounds.is_empty());
+    assert_eq!(empty.inspection_counts(), vec![0; 4]);
+    assert_eq!(empty.busiest(1), vec![(0, 0)]);
+}
+
+#[test]
+fn monkey_business_can_rank_any_number_of_monkeys() {
+    let counts = [101, 95, 7, 105];
+    assert_eq!(
+        day11::busiest(&counts, 3),
+        vec![(3, 105), (0, 101), (1, 95)]
+    );
+    assert_eq!(day11::busiest(&[4, 6, 6], 2), vec![(1, 6), (2, 6)]);
+    assert_eq!(day11::monkey_business_level(&counts, 2).unwrap(), 10605);
+    assert_eq!(
+        day11::monkey_business_level(&counts, 3).unwrap(),
+        105 * 101 * 95
+    );
+}
@@ -0,0 +1,187 @@
+//! Checks day 9's ropes with any number of knots, physics and dimensions.
+
+use std::collections::BTreeSet;
+
+use aoc2022::day9::{self, Connectivity, Physics, Rope};
+use aoc2022::Point;
+
+#[test]
+fn ropes_can_move_arbitrarily_far_from_the_start() {
+    let moves = day9::parse("L 2000\nD 1500\nR 10\n").unwrap();
+    assert_eq!(day9::part1(&moves).unwrap(), 2000 + 1499 + 9);
+}
+
+#[test]
+fn one_rope_gives_the_tail_of_every_shorter_rope() {
+    let moves = day9::parse(include_str!("../input/day9/sample1.txt")).unwrap();
+    let rope = day9::simulate::<Point>(&moves, 10, Physics::default()).unwrap();
+    assert_eq!(rope.knots().len(), 10);
+    assert_eq!(rope.visited(1).len(), 88);
+    assert_eq!(rope.visited(9).len(), 36);
+    for knots in 2..=10 {
+        assert_eq!(
+            day9::simulate::<Point>(&moves, knots, Physics::default())
+                .unwrap()
+                .visited(knots - 1),
+            rope.visited(knots - 1),
+            "{} knots",
+            knots,
+        );
+    }
+}
+
+#[test]
+fn ropes_can_have_any_number_of_knots() {
+    let moves = day9::parse("R 4\nU 4\n").unwrap();
+    let head_only = day9::simulate::<Point>(&moves, 1, Physics::default()).unwrap();
+    assert_eq!(head_only.head(), Point::new(4, -4));
+    assert_eq!(head_only.visited(0).len(), 9);
+
+    let long = day9::simulate::<Point>(&moves, 30, Physics::default()).unwrap();
+    assert_eq!(long.tail(), Point::ORIGIN);
+    assert_eq!(long.visited(29).len(), 1);
+    assert_eq!(
+        long.knots()[..5],
+        [
+            Point::new(4, -4),
+            Point::new(4, -3),
+            Point::new(4, -2),
+            Point::new(3, -2),
+            Point::new(2, -2),
+        ]
+    );
+}
+
+#[test]
+fn ropes_can_move_diagonally() {
+    let moves = day9::parse("R 2\nUR 2\nDL 1\n").unwrap();
+    let rope = day9::simulate::<Point>(&moves, 2, Physics::default()).unwrap();
+    assert_eq!(rope.head(), Point::new(3, -1));
+    assert_eq!(rope.tail(), Point::new(3, -2));
+    assert_eq!(rope.visited(1).len(), 4);
+}
+
+#[test]
+fn rope_physics_can_be_changed_at_run_time() {
+    let moves = day9::parse("R 5\n").unwrap();
+    let slack = Physics {
+        slack: 2,
+        ..Physics::default()
+    };
+    let rope = day9::simulate::<Point>(&moves, 2, slack).unwrap();
+    assert_eq!(rope.tail(), Point::new(3, 0));
+    assert_eq!(rope.visited(1).len(), 4);
+
+    // Diagonal neighbours are too far apart, so the tail has to catch up.
+    let moves = day9::parse("R 1\nU 1\n").unwrap();
+    let four = Physics {
+        connectivity: Connectivity::Four,
+        ..Physics::default()
+    };
+    assert_eq!(
+        day9::simulate::<Point>(&moves, 2, four).unwrap().tail(),
+        Point::new(1, -1)
+    );
+    let four_orthogonal = Physics {
+        diagonal_steps: false,
+        ..four
+    };
+    assert_eq!(
+        day9::simulate::<Point>(&moves, 2, four_orthogonal)
+            .unwrap()
+            .tail(),
+        Point::new(1, 0)
+    );
+
+    // Knots that can only step orthogonally close the larger gap, which here
+    // is the vertical one.
+    let moves = day9::parse("R 1\nU 2\n").unwrap();
+    let orthogonal = Physics {
+        diagonal_steps: false,
+        ..Physics::default()
+    };
+    let rope = day9::simulate::<Point>(&moves, 2, orthogonal).unwrap();
+    assert_eq!(rope.tail(), Point::new(0, -1));
+    assert_eq!(
+        rope.visited(1).iter().copied().collect::<BTreeSet<_>>(),
+        [Point::new(0, 0), Point::new(0, -1)].into_iter().collect(),
+    );
+}
+
+#[test]
+fn knots_that_catch_up_in_several_steps_visit_every_cell_on_the_way() {
+    // The head pulls a whole diagonal step ahead, so an orthogonal tail needs
+    // two steps to catch up.
+    let moves = day9::parse("UL 2\n").unwrap();
+    let orthogonal = Physics {
+        diagonal_steps: false,
+        ..Physics::default()
+    };
+    let rope = day9::simulate::<Point>(&moves, 2, orthogonal).unwrap();
+    assert_eq!(rope.tail(), Point::new(-1, -1));
+    assert_eq!(
+        rope.visited(1).iter().copied().collect::<BTreeSet<_>>(),
+        [Point::new(0, 0), Point::new(-1, 0), Point::new(-1, -1)]
+            .into_iter()
+            .collect(),
+    );
+}
+
+#[test]
+fn ropes_render_like_the_puzzle() {
+    let moves = day9::parse(include_str!("../input/day9/sample0.txt")).unwrap();
+    assert_eq!(
+        day9::simulate::<Point>(&moves, 2, Physics::default())
+            .unwrap()
+            .render(),
+        "..##.\n...##\n.1H##\n....#\ns###.",
+    );
+
+    // Partway through, fitted to just the knots and the start.
+    let mut rope: Rope = Rope::new(10, Physics::default());
+    rope.apply(moves[0]).unwrap();
+    assert_eq!(rope.render(), "4321H");
+    rope.apply(moves[1]).unwrap();
+    assert_eq!(rope.render(), "....H\n....1\n..432\n.5...\n6....");
+    assert_eq!(rope.render_trail(0), "....H\n....1\n..432\n.5..#\n6####");
+}
+
+#[test]
+fn ropes_work_in_three_dimensions() {
+    let moves = day9::parse("R 2\nF 3\nU 1\nB 1\n").unwrap();
+    let rope = day9::simulate::<[i64; 3]>(&moves, 2, Physics::default()).unwrap();
+    assert_eq!(rope.head(), [2, -1, 2]);
+    assert_eq!(rope.tail(), [2, 0, 2]);
+    assert_eq!(
+        rope.visited(1).iter().copied().collect::<BTreeSet<_>>(),
+        [[0, 0, 0], [1, 0, 0], [2, 0, 1], [2, 0, 2]]
+            .into_iter()
+            .collect(),
+    );
+
+    // Planar moves give the same tail as in 2D.
+    let moves = day9::parse(include_str!("../input/day9/sample1.txt")).unwrap();
+    let flat = day9::simulate::<[i64; 3]>(&moves, 10, Physics::default()).unwrap();
+    assert_eq!(flat.visited(9).len(), 36);
+    assert!(flat.visited(9).iter().all(|cell| cell[2] == 0));
+
+    // Orthogonal-only knots never step along two axes at once.
+    let moves = day9::parse("F 1\nU 1\nR 1\nF 1\n").unwrap();
+    let orthogonal = Physics {
+        diagonal_steps: false,
+        ..Physics::default()
+    };
+    let rope = day9::simulate::<[i64; 3]>(&moves, 2, orthogonal).unwrap();
+    assert_eq!(rope.head(), [1, -1, 2]);
+    assert_eq!(rope.tail(), [0, 0, 1]);
+}
+
+#[test]
+fn planar_ropes_reject_moves_along_the_third_axis() {
+    let moves = day9::parse("R 1\nF 2\n").unwrap();
+    let err = day9::simulate::<Point>(&moves, 2, Physics::default()).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "`Forward(2)` moves along axis 2, but the rope only has 2 dimensions"
+    );
+}
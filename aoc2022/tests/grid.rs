@@ -0,0 +1,59 @@
+//! Checks the shared `Grid` against small hand-drawn maps.
+
+use aoc2022::Grid;
+
+fn grid(s: &str) -> Grid<char> {
+    s.parse().unwrap()
+}
+
+#[test]
+fn grids_parse_from_character_maps_and_display_the_same_way() {
+    let g = grid("abc\ndef\n");
+    assert_eq!((g.width(), g.height()), (3, 2));
+    assert_eq!(g[(1, 2)], 'f');
+    assert_eq!(g.get((2, 0)), None);
+    assert_eq!(g.get((0, 3)), None);
+    assert_eq!(g.to_string(), "abc\ndef");
+}
+
+#[test]
+fn ragged_rows_are_rejected() {
+    let err = "abc\nde\n".parse::<Grid<char>>().unwrap_err();
+    assert_eq!((err.line, err.expected.as_str()), (2, "a row of 3 cells"));
+}
+
+#[test]
+fn neighbours_stay_inside_the_grid() {
+    let g = grid("abc\ndef\nghi");
+    let at = |positions: Vec<(usize, usize)>| {
+        positions.into_iter().map(|pos| g[pos]).collect::<String>()
+    };
+    assert_eq!(at(g.neighbours4((0, 0)).collect()), "bd");
+    assert_eq!(at(g.neighbours4((1, 1)).collect()), "bdfh");
+    assert_eq!(at(g.neighbours8((0, 2)).collect()), "bef");
+    assert_eq!(at(g.neighbours8((1, 1)).collect()), "abcdfghi");
+}
+
+#[test]
+fn rows_and_columns_iterate_in_order() {
+    let g = grid("abc\ndef");
+    let rows = g
+        .rows()
+        .map(|row| row.iter().collect::<String>())
+        .collect::<Vec<_>>();
+    let columns = g
+        .columns()
+        .map(|col| col.collect::<String>())
+        .collect::<Vec<_>>();
+    assert_eq!(rows, ["abc", "def"]);
+    assert_eq!(columns, ["ad", "be", "cf"]);
+}
+
+#[test]
+fn grids_transpose_and_rotate() {
+    let g = grid("abc\ndef");
+    assert_eq!(g.transpose().to_string(), "ad\nbe\ncf");
+    assert_eq!(g.rotate_clockwise().to_string(), "da\neb\nfc");
+    assert_eq!(g.rotate_counterclockwise().to_string(), "cf\nbe\nad");
+    assert_eq!(g.rotate_clockwise().rotate_counterclockwise(), g);
+}
@@ -0,0 +1,21 @@
+//! Checks that named inputs resolve to files under the input root.
+
+use aoc2022::input;
+
+#[test]
+fn named_inputs_resolve_under_the_day_directory() {
+    let path = input::resolve(input::DEFAULT_ROOT, 9, "sample1").unwrap();
+    assert_eq!(
+        path,
+        input::day_dir(input::DEFAULT_ROOT, 9).join("sample1.txt")
+    );
+}
+
+#[test]
+fn missing_inputs_list_the_available_ones() {
+    let err = input::resolve(input::DEFAULT_ROOT, 9, "sample3").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "There is no input `sample3` for day 9; available inputs are: main, sample0, sample1.",
+    );
+}
@@ -0,0 +1,138 @@
+//! Checks that malformed inputs report where they went wrong.
+
+use aoc2022::{AocParse, ParseError};
+
+fn parse_error(day: usize, input: &str) -> ParseError {
+    let solution = aoc2022::solution::get(day).unwrap();
+    let err = solution.parse(input).expect_err("input should not parse");
+    err.downcast::<ParseError>()
+        .unwrap_or_else(|err| panic!("expected a ParseError, got {:?}", err))
+}
+
+#[test]
+fn parse_errors_point_at_the_offending_text() {
+    let cases = [
+        (1, "100\n200\n\n3OO\n", 4, 0..3, "3OO"),
+        (2, "A Y\nB Q\n", 2, 2..3, "Q"),
+        (2, "A Y\nD X\n", 2, 0..1, "D"),
+        (2, "A Y\nA Y Z\n", 2, 0..5, "A Y Z"),
+        (3, "vJrwpWtwJgWr\nabc1\n", 2, 3..4, "1"),
+        (3, "abc\nabéd\n", 2, 2..4, "é"),
+        (4, "2-4,6-8\n2-4,6-x\n", 2, 6..7, "x"),
+        (4, "2-4,6-8\n2-4\n", 2, 0..3, "2-4"),
+        (4, "2-4,6-8\n2-4,6\n", 2, 4..5, "6"),
+        (9, "R 4\nU 2\nQ 1\n", 3, 0..3, "Q 1"),
+        (9, "R 4\nU x\n", 2, 2..3, "x"),
+        (10, "noop\naddx 3\naddx x\n", 3, 5..6, "x"),
+        (10, "noop\nsubx 3\n", 2, 0..6, "subx 3"),
+        (12, "Sab\nab!E\n", 2, 2..3, "!"),
+        (
+            5,
+            "    [D]\n[N] [C]\n 1   2\n\nmove 1 from 2 to 1\nmove 1 frm 1 to 2\n",
+            6,
+            0..17,
+            "move 1 frm 1 to 2",
+        ),
+        (5, "[Z] (M)\n 1   2\n\nmove 1 from 2 to 1\n", 1, 4..7, "(M)"),
+        (5, "[Z] [M]\n 1   2\n\nmove 1 from 3 to 1\n", 4, 12..13, "3"),
+        (5, "[Z] [M]\n 1   2\n\nmove 1 from 2 to 0\n", 4, 17..18, "0"),
+        (11, "Monkey 1:\n", 1, 7..8, "1"),
+        (
+            11,
+            "Monkey 0:\n  Test: divisible by 0\n",
+            2,
+            21..22,
+            "0",
+        ),
+        (
+            11,
+            "Monkey 0:\n  Starting items: 1\n  Operation: new = (old + 1\n",
+            3,
+            27..27,
+            "",
+        ),
+        (
+            11,
+            "Monkey 0:\n  Starting items: 1\n  Operation: new = old ^ 2\n",
+            3,
+            23..24,
+            "^",
+        ),
+        (
+            11,
+            "Monkey 0:\n  Starting items: 1\n  Test: divisible by 2\n  Whatever: 3\n",
+            4,
+            2..13,
+            "Whatever: 3",
+        ),
+        (
+            11,
+            "Monkey 0:\n  Starting items: 1\n  Operation: new = old + 1\n  Test: divisible by 2\n    If true: throw to monkey 0\n",
+            5,
+            30..30,
+            "",
+        ),
+    ];
+    for (day, input, line, columns, found) in cases {
+        let err = parse_error(day, input);
+        assert_eq!(
+            (err.line, err.columns, err.found.as_str()),
+            (line, columns, found),
+            "day {} input {:?}",
+            day,
+            input,
+        );
+    }
+}
+
+#[test]
+fn parse_errors_display_file_line_and_column() {
+    let err = parse_error(10, "noop\naddx\n").in_file("input.txt");
+    assert_eq!(
+        err.to_string(),
+        "input.txt:2:1: expected `addx <isize>` or `noop`, found `addx`",
+    );
+}
+
+#[test]
+fn parse_errors_render_a_caret_under_the_offending_text() {
+    let input = "noop\naddx 1\naddx x\n";
+    let err = parse_error(10, input);
+    assert_eq!(
+        err.render(input),
+        "3:6: expected a valid `isize`, found `x`\n  |\n3 | addx x\n  |      ^ expected a valid `isize`",
+    );
+}
+
+#[derive(Debug, PartialEq, AocParse)]
+#[aoc(pattern = "{name} is at ({x}, {y})")]
+struct Sighting {
+    name: String,
+    x: i32,
+    y: i32,
+}
+
+#[test]
+fn derived_parsers_match_text_and_parse_each_field() {
+    let sighting = "elf is at (-3, 12)".parse::<Sighting>().unwrap();
+    assert_eq!(
+        sighting,
+        Sighting {
+            name: "elf".to_string(),
+            x: -3,
+            y: 12
+        }
+    );
+
+    let err = "elf is at (-3, twelve)".parse::<Sighting>().unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "1:16: expected a valid `i32`, found `twelve`"
+    );
+
+    let err = "elf is at (-3, 12) now".parse::<Sighting>().unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "1:1: expected `<name> is at (<x>, <y>)`, found `elf is at (-3, 12) now`",
+    );
+}
@@ -0,0 +1,33 @@
+//! Checks `Point` arithmetic and the directions built on it.
+
+use aoc2022::point::Direction8;
+use aoc2022::{Direction, Point};
+
+#[test]
+fn points_measure_distances_and_step_towards_each_other() {
+    let a = Point::new(-2, 3);
+    let b = Point::new(1, -1);
+    assert_eq!(b - a, Point::new(3, -4));
+    assert_eq!(a.manhattan(b), 7);
+    assert_eq!(a.chebyshev(b), 4);
+    assert_eq!(a.step_towards(b), Point::new(-1, 2));
+    assert_eq!(a.step_towards(a), a);
+}
+
+#[test]
+fn points_convert_to_and_from_grid_positions() {
+    assert_eq!(Point::from((2, 5)), Point::new(5, 2));
+    assert_eq!(<(usize, usize)>::try_from(Point::new(5, 2)), Ok((2, 5)));
+    assert!(<(usize, usize)>::try_from(Point::new(-1, 2)).is_err());
+}
+
+#[test]
+fn directions_turn_and_offset() {
+    assert_eq!(Direction::Up.turn_right(), Direction::Right);
+    assert_eq!(Direction::Up.turn_left(), Direction::Left);
+    assert_eq!(Direction::Left.opposite(), Direction::Right);
+    let sum = Direction8::ALL
+        .iter()
+        .fold(Point::ORIGIN, |sum, d| sum + d.offset());
+    assert_eq!(sum, Point::ORIGIN);
+}
@@ -0,0 +1,63 @@
+//! Checks the generic searches against small hand-built graphs.
+
+use aoc2022::search;
+use aoc2022::Grid;
+
+/// Orthogonal steps between the open (`.`) cells of `maze`.
+fn open_neighbours(maze: &Grid<char>) -> impl FnMut(&(usize, usize)) -> Vec<(usize, usize)> + '_ {
+    |pos| {
+        maze.neighbours4(*pos)
+            .filter(|next| maze[*next] == '.')
+            .collect()
+    }
+}
+
+#[test]
+fn bfs_finds_the_shortest_path_around_walls() {
+    let maze: Grid<char> = ".#...\n.#.#.\n...#.".parse().unwrap();
+    let found = search::bfs([(0, 0)], open_neighbours(&maze), |pos| *pos == (0, 4));
+    assert_eq!(found.goal_distance(), Some(8));
+    let path = found.goal_path().unwrap();
+    assert_eq!(path.first(), Some(&(0, 0)));
+    assert_eq!(path.last(), Some(&(0, 4)));
+    assert_eq!(path.len(), 9);
+}
+
+#[test]
+fn bfs_from_several_starts_measures_from_the_nearest() {
+    let maze: Grid<char> = ".....".parse().unwrap();
+    let found = search::bfs([(0, 0), (0, 4)], open_neighbours(&maze), |_| false);
+    let distances = (0..5)
+        .map(|col| found.distance(&(0, col)))
+        .collect::<Vec<_>>();
+    assert_eq!(distances, [0, 1, 2, 1, 0].map(Some));
+    assert_eq!(found.goal(), None);
+}
+
+#[test]
+fn dijkstra_and_astar_prefer_cheaper_detours() {
+    // a -> b -> d costs 2, a -> c -> d costs 5, and a -> d directly costs 10.
+    let edges = |node: &char| match node {
+        'a' => vec![('b', 1), ('c', 1), ('d', 10)],
+        'b' => vec![('d', 1)],
+        'c' => vec![('d', 4)],
+        _ => vec![],
+    };
+    let dijkstra = search::dijkstra(['a'], edges, |node| *node == 'd');
+    assert_eq!(dijkstra.goal_distance(), Some(2));
+    assert_eq!(dijkstra.goal_path(), Some(vec!['a', 'b', 'd']));
+
+    let heuristic = |node: &char| if *node == 'd' { 0 } else { 1 };
+    let astar = search::astar(['a'], edges, heuristic, |node| *node == 'd');
+    assert_eq!(astar.goal_distance(), Some(2));
+    assert_eq!(astar.goal_path(), Some(vec!['a', 'b', 'd']));
+}
+
+#[test]
+fn unreachable_nodes_have_no_distance_or_path() {
+    let found = search::dijkstra([1u32], |_| Vec::<(u32, u32)>::new(), |_| false);
+    assert_eq!(found.distance(&1), Some(0));
+    assert_eq!(found.path_to(&1), Some(vec![1]));
+    assert_eq!(found.distance(&2), None);
+    assert_eq!(found.path_to(&2), None);
+}